@@ -1,8 +1,18 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
 use redis_module::{
-    redis_module, AclPermissions, Context, NextArg, RedisError, RedisResult, RedisString,
-    RedisValue,
+    redis_module, AclChannelPermissions, AclLogReason, AclPermissions, Context, ModuleUser,
+    NextArg, RedisError, RedisResult, RedisString, RedisValue,
 };
 
+/// The users created by `sso.login`, by name.
+static USERS: Mutex<Option<HashMap<String, ModuleUser>>> = Mutex::new(None);
+
+/// The number of clients deauthenticated from the users of `sso.login`.
+static DEAUTHENTICATED: AtomicUsize = AtomicUsize::new(0);
+
 fn verify_key_access_for_user(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     let mut args = args.into_iter().skip(1);
     let user = args.next_arg()?;
@@ -14,10 +24,120 @@ fn verify_key_access_for_user(ctx: &Context, args: Vec<RedisString>) -> RedisRes
     Ok(RedisValue::SimpleStringStatic("OK"))
 }
 
+fn verify_command_access_for_user(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 3 {
+        return Err(RedisError::WrongArity);
+    }
+    let user = &args[1];
+    let command: Vec<&RedisString> = args[2..].iter().collect();
+    let res = ctx.acl_check_command_permission(user, &command);
+    if let Err(err) = res {
+        return Err(RedisError::String(format!("Err {err}")));
+    }
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+fn verify_channel_access_for_user(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let user = args.next_arg()?;
+    let channel = args.next_arg()?;
+    let res = ctx.acl_check_channel_permission(&user, &channel, &AclChannelPermissions::PUBLISH);
+    if let Err(err) = res {
+        return Err(RedisError::String(format!("Err {err}")));
+    }
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+// guarded_get key
+// Returns the value of the key, if the current user can access it. Denials
+// are added to ACL LOG.
+fn guarded_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    args.done()?;
+
+    let user = ctx.get_current_user();
+    if ctx
+        .acl_check_key_permission(&user, &key_name, &AclPermissions::ACCESS)
+        .is_err()
+    {
+        ctx.acl_add_log_entry(&user, &key_name, AclLogReason::Key);
+        return Err(RedisError::Str(
+            "NOPERM this user has no permissions to access the key",
+        ));
+    }
+    let key = ctx.open_key(&key_name);
+    Ok(key.read()?.map_or(RedisValue::Null, |value| {
+        RedisValue::StringBuffer(value.to_vec())
+    }))
+}
+
 fn get_current_user(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::BulkRedisString(ctx.get_current_user()))
 }
 
+// sso.login name rules
+// Authenticates the client as a module user created with the given ACL
+// rules, returns the id of the client.
+fn sso_login(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let name = args.next_string()?;
+    let rules = args.next_string()?;
+    args.done()?;
+
+    let user = ModuleUser::new(&name);
+    user.set_acl_string(ctx, &rules)?;
+    let client_id = ctx.authenticate_client_with_user(&user, |_client_id| {
+        DEAUTHENTICATED.fetch_add(1, Ordering::Relaxed);
+    })?;
+
+    // A previous user with the same name disconnects its clients when
+    // dropped, outside of the lock as it calls the callback above.
+    let previous = USERS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(name, user);
+    drop(previous);
+    Ok((client_id as i64).into())
+}
+
+// sso.acl name
+// Returns the ACL rules of the user created by sso.login.
+fn sso_acl(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let name = args.next_string()?;
+    args.done()?;
+
+    let users = USERS.lock().unwrap();
+    let user = users
+        .as_ref()
+        .and_then(|users| users.get(&name))
+        .ok_or(RedisError::Str("ERR no such user"))?;
+    Ok(user.acl_string().into())
+}
+
+// sso.logout name
+// Deletes the user created by sso.login, which disconnects its clients.
+fn sso_logout(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let name = args.next_string()?;
+    args.done()?;
+
+    let user = USERS
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|users| users.remove(&name));
+    Ok(user.is_some().into())
+}
+
+// sso.deauthenticated
+// Returns the number of clients deauthenticated from the users of sso.login.
+fn sso_deauthenticated(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(DEAUTHENTICATED.load(Ordering::Relaxed).into())
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -27,6 +147,13 @@ redis_module! {
     data_types: [],
     commands: [
         ["verify_key_access_for_user", verify_key_access_for_user, "", 0, 0, 0],
+        ["verify_command_access_for_user", verify_command_access_for_user, "", 0, 0, 0],
+        ["verify_channel_access_for_user", verify_channel_access_for_user, "", 0, 0, 0],
         ["get_current_user", get_current_user, "", 0, 0, 0],
+        ["guarded_get", guarded_get, "readonly", 0, 0, 0, "read"],
+        ["sso.login", sso_login, "no-auth", 0, 0, 0],
+        ["sso.acl", sso_acl, "", 0, 0, 0],
+        ["sso.logout", sso_logout, "", 0, 0, 0],
+        ["sso.deauthenticated", sso_deauthenticated, "", 0, 0, 0],
     ],
 }
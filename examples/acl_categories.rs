@@ -0,0 +1,47 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString};
+use redis_module_macros::command;
+
+/// The balance of the account, changed with `accounts.deposit`.
+static BALANCE: AtomicI64 = AtomicI64::new(0);
+
+// accounts.balance
+// Returns the balance of the account.
+fn accounts_balance(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(BALANCE.load(Ordering::Relaxed).into())
+}
+
+// accounts.deposit amount
+// Adds the amount to the balance of the account, returns the new balance.
+#[command(
+    {
+        name: "accounts.deposit",
+        flags: [Write, Fast],
+        acl_categories: ["write", "fast", "accounts"],
+        arity: 2,
+        key_spec: []
+    }
+)]
+fn accounts_deposit(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let amount = args.next_i64()?;
+    args.done()?;
+
+    Ok((BALANCE.fetch_add(amount, Ordering::Relaxed) + amount).into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "acl_categories",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    acl_categories: [
+        "accounts",
+    ],
+    commands: [
+        ["accounts.balance", accounts_balance, "readonly fast", 0, 0, 0, "read fast accounts"],
+    ],
+}
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use redis_module::{redis_module, Context, RedisArgs, RedisResult, RedisString, RedisValue};
+use redis_module_macros::{command, RedisArgs};
+
+/// The scores of the members of each board, changed with `scores.add`.
+static BOARDS: Mutex<Option<HashMap<String, HashMap<String, f64>>>> = Mutex::new(None);
+
+#[derive(RedisArgs)]
+enum Condition {
+    /// Only add new members.
+    Nx,
+    /// Only update existing members.
+    Xx,
+}
+
+#[derive(RedisArgs)]
+struct ScoreMember {
+    score: f64,
+    member: String,
+}
+
+// board [NX | XX] score member [score member ...]
+#[derive(RedisArgs)]
+struct AddArgs {
+    #[RedisArgsAttr{key_spec_index: 0}]
+    board: String,
+    condition: Option<Condition>,
+    #[RedisArgsAttr{name: "data", block: true}]
+    members: Vec<ScoreMember>,
+}
+
+#[derive(RedisArgs)]
+struct Limit {
+    offset: usize,
+    count: usize,
+}
+
+// board [MIN min] [LIMIT offset count] [WITHSCORES]
+#[derive(RedisArgs)]
+struct RangeArgs {
+    #[RedisArgsAttr{key_spec_index: 0}]
+    board: String,
+    #[RedisArgsAttr{token: "MIN", summary: "The lowest score to return"}]
+    min: Option<f64>,
+    #[RedisArgsAttr{token: "LIMIT", block: true}]
+    limit: Option<Limit>,
+    #[RedisArgsAttr{token: "WITHSCORES"}]
+    with_scores: bool,
+}
+
+// board BY increment member [member ...]
+#[derive(RedisArgs)]
+struct IncrArgs {
+    #[RedisArgsAttr{key_spec_index: 0}]
+    board: String,
+    #[RedisArgsAttr{token: "BY"}]
+    increment: f64,
+    members: Vec<String>,
+}
+
+// scores.add board [NX | XX] score member [score member ...]
+// Sets the scores of the members of the board, returns the number of
+// members which were added.
+#[command(
+    {
+        name: "scores.add",
+        flags: [Write, Fast],
+        arity: -4,
+        key_spec: [
+            {
+                notes: "the board",
+                flags: [ReadWrite, Insert, Update],
+                begin_search: Index({ index : 1 }),
+                find_keys: Range({ last_key : 0, steps : 1, limit : 0 }),
+            }
+        ],
+        args_type: "AddArgs"
+    }
+)]
+fn scores_add(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let args = AddArgs::from_args(args.into_iter().skip(1))?;
+
+    let mut boards = BOARDS.lock().unwrap();
+    let board = boards
+        .get_or_insert_with(HashMap::new)
+        .entry(args.board)
+        .or_default();
+    let mut added: i64 = 0;
+    for ScoreMember { score, member } in args.members {
+        match (board.get_mut(&member), &args.condition) {
+            (Some(_), Some(Condition::Nx)) | (None, Some(Condition::Xx)) => {}
+            (Some(current), _) => *current = score,
+            (None, _) => {
+                board.insert(member, score);
+                added += 1;
+            }
+        }
+    }
+    Ok(added.into())
+}
+
+// scores.incr board BY increment member [member ...]
+// Increments the scores of the members of the board, adding the missing
+// ones, returns their new scores.
+#[command(
+    {
+        name: "scores.incr",
+        flags: [Write, Fast],
+        arity: -5,
+        key_spec: [
+            {
+                notes: "the board",
+                flags: [ReadWrite, Insert, Update],
+                begin_search: Index({ index : 1 }),
+                find_keys: Range({ last_key : 0, steps : 1, limit : 0 }),
+            }
+        ],
+        args_type: "IncrArgs"
+    }
+)]
+fn scores_incr(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let args = IncrArgs::from_args(args.into_iter().skip(1))?;
+
+    let mut boards = BOARDS.lock().unwrap();
+    let board = boards
+        .get_or_insert_with(HashMap::new)
+        .entry(args.board)
+        .or_default();
+    let reply = args
+        .members
+        .into_iter()
+        .map(|member| {
+            let score = board.entry(member).or_insert(0.0);
+            *score += args.increment;
+            RedisValue::Float(*score)
+        })
+        .collect::<Vec<RedisValue>>();
+    Ok(reply.into())
+}
+
+// scores.range board [MIN min] [LIMIT offset count] [WITHSCORES]
+// Returns the members of the board ordered by their scores.
+#[command(
+    {
+        name: "scores.range",
+        flags: [ReadOnly],
+        arity: -2,
+        key_spec: [
+            {
+                notes: "the board",
+                flags: [ReadOnly, Access],
+                begin_search: Index({ index : 1 }),
+                find_keys: Range({ last_key : 0, steps : 1, limit : 0 }),
+            }
+        ],
+        args_type: "RangeArgs"
+    }
+)]
+fn scores_range(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let args = RangeArgs::from_args(args.into_iter().skip(1))?;
+
+    let boards = BOARDS.lock().unwrap();
+    let mut members: Vec<_> = boards
+        .as_ref()
+        .and_then(|boards| boards.get(&args.board))
+        .into_iter()
+        .flatten()
+        .filter(|(_, score)| !matches!(args.min, Some(min) if **score < min))
+        .collect();
+    members.sort_by(|(m1, s1), (m2, s2)| s1.total_cmp(s2).then(m1.cmp(m2)));
+    let (offset, count) = args
+        .limit
+        .map_or((0, members.len()), |limit| (limit.offset, limit.count));
+
+    let reply = members
+        .into_iter()
+        .skip(offset)
+        .take(count)
+        .flat_map(|(member, score)| {
+            let score = args.with_scores.then(|| RedisValue::Float(*score));
+            std::iter::once(member.as_str().into()).chain(score)
+        })
+        .collect::<Vec<RedisValue>>();
+    Ok(reply.into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "args",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [],
+}
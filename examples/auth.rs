@@ -0,0 +1,65 @@
+use std::thread;
+use std::time::Duration;
+
+use redis_module::{redis_module, AuthResult, Context, RedisResult, RedisString, RedisValue};
+
+/// The users of the directory, standing in for an LDAP server, with their
+/// passwords.
+const DIRECTORY: &[(&str, &str)] = &[("ldap_alice", "alice_pass"), ("ldap_bob", "bob_pass")];
+
+/// Authenticates the users of the module itself, without blocking.
+fn local_auth(_ctx: &Context, username: RedisString, password: RedisString) -> AuthResult {
+    if username.as_slice() != b"local_admin" {
+        return AuthResult::Skip;
+    }
+    if password.as_slice() == b"admin_pass" {
+        AuthResult::Allow
+    } else {
+        AuthResult::Deny("Invalid password for local_admin".to_string())
+    }
+}
+
+/// Authenticates the users of the directory on a background thread, as an
+/// LDAP lookup would.
+fn directory_auth(ctx: &Context, username: RedisString, password: RedisString) -> AuthResult {
+    let (username, password) = (username.to_string_lossy(), password.to_string_lossy());
+    if !username.starts_with("ldap_") {
+        return AuthResult::Skip;
+    }
+    let blocked_client = match ctx.block_client_on_auth() {
+        Ok(blocked_client) => blocked_client,
+        Err(e) => return AuthResult::Deny(e.to_string()),
+    };
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        let result = if DIRECTORY.contains(&(username.as_str(), password.as_str())) {
+            AuthResult::Allow
+        } else {
+            AuthResult::Deny(format!("Invalid directory credentials for {username}"))
+        };
+        blocked_client.unblock(result);
+    });
+    AuthResult::Blocked
+}
+
+// auth.whoami
+// Returns the name of the user the client is authenticated as.
+fn auth_whoami(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::BulkRedisString(ctx.get_current_user()))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "auth",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["auth.whoami", auth_whoami, "", 0, 0, 0],
+    ],
+    auth: [
+        local_auth,
+        directory_auth,
+    ],
+}
@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+
+use redis_module::native_types::AuxSaveTriggers;
+use redis_module::{
+    redis_module, AuxData, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+    Status,
+};
+
+/// The catalog of the module, not stored in any key. It is saved in the RDB
+/// so it survives restarts and reaches the replicas on full sync.
+#[derive(Default)]
+struct Catalog {
+    entries: BTreeMap<String, String>,
+}
+
+fn catalog() -> Result<AuxData<Catalog>, RedisError> {
+    AuxData::get("catalog01").ok_or(RedisError::Str("ERR the catalog is not registered"))
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    let res = ctx
+        .aux_data(
+            "catalog01",
+            Catalog::default(),
+            |catalog, rdb, _when| {
+                // Serializing a map of strings cannot fail.
+                rdb.serialize(&catalog.entries).unwrap();
+            },
+            |catalog, rdb, _when| {
+                catalog.entries = rdb.deserialize()?;
+                Ok(())
+            },
+        )
+        .encver(1)
+        .triggers(AuxSaveTriggers::BEFORE_RDB)
+        .register();
+    match res {
+        Ok(_) => Status::Ok,
+        Err(_) => Status::Err,
+    }
+}
+
+// CATALOG.SET name value
+// Sets the value of the catalog entry.
+fn catalog_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let name = args.next_string()?;
+    let value = args.next_string()?;
+    args.done()?;
+
+    catalog()?.lock(ctx).entries.insert(name, value);
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+// CATALOG.GET name
+// Returns the value of the catalog entry, or null.
+fn catalog_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let name = args.next_string()?;
+    args.done()?;
+
+    Ok(catalog()?.lock(ctx).entries.get(&name).cloned().into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "aux_data",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [
+        ["CATALOG.SET", catalog_set, "write deny-oom", 0, 0, 0],
+        ["CATALOG.GET", catalog_get, "readonly", 0, 0, 0],
+    ],
+}
@@ -1,6 +1,9 @@
 use redis_module::{
-    redis_module, Context, RedisResult, RedisString, RedisValue, ThreadSafeContext,
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+    ThreadSafeContext,
 };
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
@@ -17,6 +20,57 @@ fn block(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::NoReply)
 }
 
+// block.sleep ms timeout_ms
+// Sleeps in a background thread, in steps of 10 ms, and replies with the
+// number of steps. Gives up when the client disconnects.
+fn block_sleep(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let ms = args.next_u64()?;
+    let timeout = args.next_u64()?;
+    args.done()?;
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let on_disconnect = {
+        let cancelled = Arc::clone(&cancelled);
+        move |_ctx: &Context| cancelled.store(true, Ordering::Relaxed)
+    };
+    let blocked_client = ctx
+        .block_client_with_reply(|_ctx, steps: u64| Ok(RedisValue::Integer(steps as i64)))
+        .timeout(Duration::from_millis(timeout))
+        .on_timeout(|_ctx| Err(RedisError::Str("ERR timed out")))
+        .on_disconnect(on_disconnect)
+        .block();
+
+    thread::spawn(move || {
+        // Account the time spent sleeping to the command, so it shows up in
+        // SLOWLOG.
+        blocked_client.measure_time_start();
+        let mut steps = 0;
+        while steps * 10 < ms && !cancelled.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(10));
+            steps += 1;
+        }
+        blocked_client.measure_time_end();
+        blocked_client.unblock(steps);
+    });
+
+    // We will reply later, from the reply callback
+    Ok(RedisValue::NoReply)
+}
+
+// block.drop
+// Drops the blocked client in a background thread without unblocking it,
+// the client then gets an error.
+fn block_drop(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let blocked_client = ctx
+        .block_client_with_reply(|_ctx, value: i64| Ok(RedisValue::Integer(value)))
+        .block();
+
+    thread::spawn(move || drop(blocked_client));
+
+    Ok(RedisValue::NoReply)
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -26,5 +80,7 @@ redis_module! {
     data_types: [],
     commands: [
         ["block", block, "", 0, 0, 0],
+        ["block.sleep", block_sleep, "", 0, 0, 0],
+        ["block.drop", block_drop, "", 0, 0, 0],
     ],
 }
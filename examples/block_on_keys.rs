@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+use redis_module::{
+    redis_module, Context, NextArg, RedisResult, RedisString, RedisValue, REDIS_OK,
+};
+
+// QUEUE.PUSH key element
+fn queue_push(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let element = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key_name);
+    key.list_push_tail(element);
+    ctx.signal_key_as_ready(&key_name);
+    REDIS_OK
+}
+
+fn pop_reply(element: RedisString, waited: Duration) -> RedisValue {
+    RedisValue::Array(vec![
+        element.into(),
+        RedisValue::Integer(waited.as_millis() as i64),
+    ])
+}
+
+// QUEUE.POP key timeout_ms
+// Pops the first element of the queue, waiting up to timeout_ms for one to be
+// pushed if the queue is empty (0 waits forever). Replies with the element
+// and the time spent waiting for it.
+fn queue_pop(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let timeout = args.next_u64()?;
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key_name);
+    if let Some(element) = key.list_pop_head() {
+        return Ok(pop_reply(element, Duration::ZERO));
+    }
+
+    ctx.block_client_on_keys(&[key_name], Instant::now(), |ctx, key_name, since| {
+        // Another client may have been served first, in which case the
+        // client stays blocked.
+        let key = ctx.open_key_writable(key_name);
+        let element = key.list_pop_head()?;
+        Some(Ok(pop_reply(element, since.elapsed())))
+    })
+    .timeout(Duration::from_millis(timeout))
+    .on_timeout(|_ctx, _since| Ok(RedisValue::Null))
+    .block()?;
+
+    // We will reply later, when the queue is ready
+    Ok(RedisValue::NoReply)
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "block_on_keys",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["QUEUE.PUSH", queue_push, "write deny-oom", 1, 1, 1],
+        ["QUEUE.POP", queue_pop, "write", 1, 1, 1],
+    ],
+}
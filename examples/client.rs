@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+/// The number of calls to `client.hit`, by client identity.
+static HITS: Mutex<Option<HashMap<String, i64>>> = Mutex::new(None);
+
+/// Returns the client id given as argument, or the id of the current client.
+fn client_id_arg(ctx: &Context, args: &mut impl Iterator<Item = RedisString>) -> RedisResult<u64> {
+    args.next()
+        .map_or(Ok(ctx.client_id()), |id| id.parse_unsigned_integer())
+}
+
+// client.id
+// Returns the id of the current client.
+fn client_id(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok((ctx.client_id() as i64).into())
+}
+
+// client.info [id]
+// Returns the id, address, port, database and flags of the client.
+fn client_info(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let id = client_id_arg(ctx, &mut args)?;
+    args.done()?;
+
+    let info = ctx.client_info(id)?;
+    let flags: Vec<RedisValue> = info
+        .flags
+        .iter_names()
+        .map(|(name, _)| name.into())
+        .collect();
+    Ok(RedisValue::Array(vec![
+        "id".into(),
+        (info.id as i64).into(),
+        "addr".into(),
+        info.addr.into(),
+        "port".into(),
+        i64::from(info.port).into(),
+        "db".into(),
+        i64::from(info.db).into(),
+        "flags".into(),
+        flags.into(),
+    ]))
+}
+
+// client.name [id]
+// Returns the name of the client, or null if it has none.
+fn client_name(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let id = client_id_arg(ctx, &mut args)?;
+    args.done()?;
+
+    Ok(ctx.client_name(id).into())
+}
+
+// client.setname id name
+// Sets the name of the client.
+fn client_setname(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let id = args.next_u64()?;
+    let name = args.next_arg()?;
+    args.done()?;
+
+    ctx.set_client_name(id, &name)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+// client.username [id]
+// Returns the name of the user the client is authenticated as.
+fn client_username(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let id = client_id_arg(ctx, &mut args)?;
+    args.done()?;
+
+    Ok(ctx.client_username(id)?.into())
+}
+
+// client.cert [id]
+// Returns the TLS certificate of the client, or null if it has none.
+fn client_cert(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let id = client_id_arg(ctx, &mut args)?;
+    args.done()?;
+
+    Ok(ctx.client_certificate(id).into())
+}
+
+// client.hit
+// Counts the calls of the current client, keyed on its name or, for clients
+// without a name, on its address, as a rate limiter would. Returns the count.
+fn client_hit(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() != 1 {
+        return Err(RedisError::WrongArity);
+    }
+    let id = ctx.client_id();
+    let identity = match ctx.client_name(id) {
+        Some(name) => format!("name:{name}"),
+        None => format!("addr:{}", ctx.client_info(id)?.addr),
+    };
+
+    let mut hits = HITS.lock().unwrap();
+    let count = hits
+        .get_or_insert_with(HashMap::new)
+        .entry(identity)
+        .or_insert(0);
+    *count += 1;
+    Ok((*count).into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "client",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["client.id", client_id, "", 0, 0, 0],
+        ["client.info", client_info, "", 0, 0, 0],
+        ["client.name", client_name, "", 0, 0, 0],
+        ["client.setname", client_setname, "", 0, 0, 0],
+        ["client.username", client_username, "", 0, 0, 0],
+        ["client.cert", client_cert, "", 0, 0, 0],
+        ["client.hit", client_hit, "", 0, 0, 0],
+    ],
+}
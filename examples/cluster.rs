@@ -0,0 +1,86 @@
+use std::sync::Mutex;
+
+use redis_module::{
+    redis_module, ClusterNodeId, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+const MSG_TYPE_NOTE: u8 = 1;
+
+/// The notes received from the other nodes, as `sender:note`.
+static NOTES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn on_note(ctx: &Context, sender: &ClusterNodeId, _msg_type: u8, payload: &[u8]) {
+    let note = format!("{sender}:{}", String::from_utf8_lossy(payload));
+    ctx.log_notice(&format!("Received note {note}"));
+    NOTES.lock().unwrap().push(note);
+}
+
+// COORD.SEND note [node]
+// Sends a note to the given node, or to all the nodes of the cluster.
+fn coord_send(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let note = args.next_arg()?;
+    let target = args
+        .next()
+        .map(|node| ClusterNodeId::try_from(node.try_as_str()?))
+        .transpose()?;
+    args.done()?;
+
+    ctx.send_cluster_message(target.as_ref(), MSG_TYPE_NOTE, &note)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+// COORD.NOTES
+// Returns the notes received so far.
+fn coord_notes(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let notes = NOTES.lock().unwrap();
+    Ok(notes.clone().into())
+}
+
+// COORD.NODES
+// Returns the id, address and master of every node of the cluster.
+fn coord_nodes(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let nodes = ctx
+        .get_cluster_nodes_list()
+        .ok_or(RedisError::Str("ERR cluster mode is disabled"))?;
+    Ok(RedisValue::Array(
+        nodes
+            .iter()
+            .filter_map(|id| {
+                let info = ctx.get_cluster_node_info(id)?;
+                Some(RedisValue::Array(vec![
+                    id.to_string().into(),
+                    format!("{}:{}", info.ip, info.port).into(),
+                    info.master_id.map(|id| id.to_string()).into(),
+                ]))
+            })
+            .collect(),
+    ))
+}
+
+// COORD.INFO
+// Returns the id of the current node and the size of the cluster.
+fn coord_info(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Array(vec![
+        ctx.get_my_cluster_id().map(|id| id.to_string()).into(),
+        ctx.get_cluster_size().into(),
+    ]))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "cluster",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["COORD.SEND", coord_send, "readonly", 0, 0, 0],
+        ["COORD.NOTES", coord_notes, "readonly", 0, 0, 0],
+        ["COORD.NODES", coord_nodes, "readonly", 0, 0, 0],
+        ["COORD.INFO", coord_info, "readonly", 0, 0, 0],
+    ],
+    cluster_message_receivers: [
+        [MSG_TYPE_NOTE, on_note],
+    ],
+}
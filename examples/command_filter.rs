@@ -0,0 +1,90 @@
+use std::cell::RefCell;
+use std::ptr;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use redis_module::{
+    redis_module, CommandFilter, CommandFilterCtx, CommandFilterFlags, Context, RedisResult,
+    RedisString, RedisValue,
+};
+
+static REWRITES: AtomicI64 = AtomicI64::new(0);
+
+/// The number of commands seen by the one shot filters.
+static ONE_SHOT_HITS: AtomicI64 = AtomicI64::new(0);
+
+thread_local! {
+    /// The one shot filter registered with `FILTER.ONESHOT`. Filters and
+    /// commands run on the main thread.
+    static ONE_SHOT_FILTER: RefCell<Option<CommandFilter>> = const { RefCell::new(None) };
+}
+
+fn new_arg(arg: &[u8]) -> RedisString {
+    // Arguments handed to the filter are owned by Redis, so they must not be
+    // created with a context.
+    RedisString::create_from_slice(ptr::null_mut(), arg)
+}
+
+fn rewrite(fctx: &mut CommandFilterCtx) -> RedisResult<()> {
+    let Some(command) = fctx.arg_get(0) else {
+        return Ok(());
+    };
+
+    if command.eq_ignore_ascii_case(b"LEGACY.SET") {
+        // LEGACY.SET key value => SET key value
+        fctx.arg_replace(0, new_arg(b"SET"))?;
+    } else if command.eq_ignore_ascii_case(b"LEGACY.SETEX") && fctx.args_count() == 4 {
+        // LEGACY.SETEX key seconds value => SET key value EX seconds
+        let seconds = fctx.arg_get(2).unwrap();
+        fctx.arg_replace(0, new_arg(b"SET"))?;
+        fctx.arg_delete(2)?;
+        fctx.arg_insert(3, new_arg(b"EX"))?;
+        fctx.arg_insert(4, seconds)?;
+    } else {
+        return Ok(());
+    }
+
+    REWRITES.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+fn rewrite_legacy_commands(fctx: &mut CommandFilterCtx) {
+    // A failed rewrite leaves the command as is, Redis will then reject the
+    // unknown legacy command.
+    let _ = rewrite(fctx);
+}
+
+fn one_shot(_fctx: &mut CommandFilterCtx) {
+    ONE_SHOT_HITS.fetch_add(1, Ordering::Relaxed);
+    // Unregisters the filter from its own callback.
+    let filter = ONE_SHOT_FILTER.with(|filter| filter.borrow_mut().take());
+    drop(filter);
+}
+
+// filter.oneshot
+// Registers a filter which only sees the next command, returns the number
+// of commands seen by the previous ones.
+fn filter_one_shot(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let filter = ctx.register_command_filter(CommandFilterFlags::empty(), one_shot)?;
+    ONE_SHOT_FILTER.with(|one_shot| one_shot.borrow_mut().replace(filter));
+    Ok(RedisValue::Integer(ONE_SHOT_HITS.load(Ordering::Relaxed)))
+}
+
+fn filter_rewrites(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Integer(REWRITES.load(Ordering::Relaxed)))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "command_filter",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["FILTER.REWRITES", filter_rewrites, "readonly", 0, 0, 0],
+        ["FILTER.ONESHOT", filter_one_shot, "", 0, 0, 0],
+    ],
+    command_filters: [
+        [rewrite_legacy_commands, CommandFilterFlags::NOSELF],
+    ],
+}
@@ -0,0 +1,224 @@
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use redis_module::defrag::{defrag_callback, Defrag, DefragContext, DefragStatus};
+use redis_module::error::Error;
+use redis_module::native_types::{RedisDataType, RedisType};
+use redis_module::rdb::{RdbReader, RdbWriter};
+use redis_module::{
+    raw, redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue, Status,
+};
+use redis_module_macros::redis_data_type;
+
+static INDEX_TYPE: RedisType = RedisType::new(
+    "defragidx",
+    0,
+    raw::RedisModuleTypeMethods {
+        version: raw::REDISMODULE_TYPE_METHOD_VERSION as u64,
+        rdb_load: None,
+        rdb_save: None,
+        aof_rewrite: None,
+        free: Some(free),
+
+        mem_usage: None,
+        digest: None,
+
+        aux_load: None,
+        aux_save: None,
+        aux_save2: None,
+        aux_save_triggers: 0,
+
+        free_effort: None,
+        unlink: None,
+        copy: None,
+        defrag: Some(defrag_callback::<Index>),
+
+        copy2: None,
+        free_effort2: None,
+        mem_usage2: None,
+        unlink2: None,
+    },
+);
+
+/// The number of values defragmented, of defrag calls resumed from a
+/// cursor, of global defrag calls, and of tags values defragmented.
+static DEFRAGGED_VALUES: AtomicUsize = AtomicUsize::new(0);
+static RESUMED_DEFRAGS: AtomicUsize = AtomicUsize::new(0);
+static GLOBAL_DEFRAGS: AtomicUsize = AtomicUsize::new(0);
+static DEFRAGGED_TAGS: AtomicUsize = AtomicUsize::new(0);
+
+/// Global data of the module, not stored in any key.
+static NAMES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Index {
+    items: Vec<Vec<u8>>,
+}
+
+impl Defrag for Index {
+    fn defrag(&mut self, ctx: &DefragContext) -> DefragStatus {
+        let start = match ctx.cursor() {
+            Some(cursor) if cursor > 0 => {
+                RESUMED_DEFRAGS.fetch_add(1, Ordering::Relaxed);
+                cursor as usize
+            }
+            _ => {
+                // The module uses the Redis allocator, see redis_module! below.
+                self.items = unsafe { ctx.defrag_vec(std::mem::take(&mut self.items)) };
+                0
+            }
+        };
+
+        for pos in start..self.items.len() {
+            let item = std::mem::take(&mut self.items[pos]);
+            self.items[pos] = unsafe { ctx.defrag_vec(item) };
+            if pos + 1 < self.items.len()
+                && ctx.should_stop()
+                && ctx.set_cursor(pos as u64 + 1).is_ok()
+            {
+                return DefragStatus::Incomplete;
+            }
+        }
+        DEFRAGGED_VALUES.fetch_add(1, Ordering::Relaxed);
+        DefragStatus::Done
+    }
+}
+
+/// A set of tags, in a data type declared with `#[redis_data_type]`.
+struct Tags {
+    tags: Vec<String>,
+}
+
+#[redis_data_type({ name: "defragtag", encoding_version: 0 })]
+impl RedisDataType for Tags {
+    fn rdb_load(rdb: &mut RdbReader, _encver: i32) -> Result<Self, Error> {
+        let len = rdb.read_unsigned()?;
+        let tags = (0..len)
+            .map(|_| Ok(rdb.read_string_buffer()?.to_string()?))
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { tags })
+    }
+
+    fn rdb_save(&self, rdb: &mut RdbWriter) {
+        rdb.write_unsigned(self.tags.len() as u64);
+        self.tags.iter().for_each(|tag| rdb.write_str(tag));
+    }
+
+    fn defrag(&mut self, ctx: &DefragContext) -> DefragStatus {
+        self.tags = unsafe { ctx.defrag_vec(std::mem::take(&mut self.tags)) };
+        DEFRAGGED_TAGS.fetch_add(1, Ordering::Relaxed);
+        DefragStatus::Done
+    }
+}
+
+unsafe extern "C" fn free(value: *mut c_void) {
+    drop(Box::from_raw(value.cast::<Index>()));
+}
+
+fn defrag_names(ctx: &DefragContext) {
+    let mut names = NAMES.lock().unwrap();
+    *names = unsafe { ctx.defrag_vec(std::mem::take(&mut *names)) };
+    GLOBAL_DEFRAGS.fetch_add(1, Ordering::Relaxed);
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    match ctx.register_defrag_function(defrag_names) {
+        Ok(()) => Status::Ok,
+        Err(_) => Status::Err,
+    }
+}
+
+// DEFRAG.ADD key item [item ...]
+// Adds the items to the index stored at key.
+fn defrag_add(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 3 {
+        return Err(RedisError::WrongArity);
+    }
+    let mut args = args.into_iter().skip(1);
+    let key = ctx.open_key_writable(&args.next_arg()?);
+    let items = args.map(|item| item.as_slice().to_vec());
+
+    let len = match key.get_value::<Index>(&INDEX_TYPE)? {
+        Some(index) => {
+            index.items.extend(items);
+            index.items.len()
+        }
+        None => {
+            let index = Index {
+                items: items.collect(),
+            };
+            let len = index.items.len();
+            key.set_value(&INDEX_TYPE, index)?;
+            len
+        }
+    };
+    Ok(len.into())
+}
+
+// DEFRAG.LEN key
+// Returns the number of items of the index stored at key.
+fn defrag_len(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = ctx.open_key(&args.next_arg()?);
+    args.done()?;
+
+    let len = key
+        .get_value::<Index>(&INDEX_TYPE)?
+        .map_or(0, |index| index.items.len());
+    Ok(len.into())
+}
+
+// DEFRAG.TAG key tag [tag ...]
+// Adds the tags to the set stored at key.
+fn defrag_tag(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 3 {
+        return Err(RedisError::WrongArity);
+    }
+    let mut args = args.into_iter().skip(1);
+    let key = ctx.open_key_writable(&args.next_arg()?);
+    let tags = args.map(|tag| tag.to_string_lossy());
+
+    let len = match key.get_typed_value::<Tags>()? {
+        Some(value) => {
+            value.tags.extend(tags);
+            value.tags.len()
+        }
+        None => {
+            let value = Tags {
+                tags: tags.collect(),
+            };
+            let len = value.tags.len();
+            key.set_typed_value(value)?;
+            len
+        }
+    };
+    Ok(len.into())
+}
+
+// DEFRAG.STATS
+// Returns the number of values defragmented, of resumed defrag calls, of
+// global defrag calls and of tags values defragmented.
+fn defrag_stats(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Array(vec![
+        DEFRAGGED_VALUES.load(Ordering::Relaxed).into(),
+        RESUMED_DEFRAGS.load(Ordering::Relaxed).into(),
+        GLOBAL_DEFRAGS.load(Ordering::Relaxed).into(),
+        DEFRAGGED_TAGS.load(Ordering::Relaxed).into(),
+    ]))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "defrag",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [INDEX_TYPE],
+    init: init,
+    commands: [
+        ["DEFRAG.ADD", defrag_add, "write deny-oom", 1, 1, 1],
+        ["DEFRAG.LEN", defrag_len, "readonly", 1, 1, 1],
+        ["DEFRAG.TAG", defrag_tag, "write deny-oom", 1, 1, 1],
+        ["DEFRAG.STATS", defrag_stats, "readonly", 0, 0, 0],
+    ],
+}
@@ -0,0 +1,88 @@
+use lazy_static::lazy_static;
+use redis_module::dict::{DictSeekOp, RedisDict};
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisGILGuard, RedisResult, RedisString, RedisValue,
+};
+
+lazy_static! {
+    static ref INDEX: RedisGILGuard<RedisDict<i64>> = RedisGILGuard::default();
+}
+
+// INDEX.SET name value
+// Returns the previous value of the entry, if any.
+fn index_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let name = args.next_arg()?;
+    let value = args.next_i64()?;
+    args.done()?;
+
+    let mut index = INDEX.lock(ctx);
+    Ok(index
+        .insert(&name, value)
+        .map_or(RedisValue::Null, RedisValue::Integer))
+}
+
+// INDEX.GET name
+fn index_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let name = args.next_arg()?;
+    args.done()?;
+
+    let index = INDEX.lock(ctx);
+    Ok(index
+        .get(&name)
+        .map_or(RedisValue::Null, |v| RedisValue::Integer(*v)))
+}
+
+// INDEX.DEL name
+fn index_del(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let name = args.next_arg()?;
+    args.done()?;
+
+    let mut index = INDEX.lock(ctx);
+    Ok(RedisValue::Integer(index.remove(&name).is_some().into()))
+}
+
+// INDEX.RANGE op name count [REV]
+// Seeks to the given position (op is one of ^ $ > >= < <= ==) and returns up
+// to count entries, walking backward if REV is given.
+fn index_range(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let op = DictSeekOp::try_from(args.next_str()?)?;
+    let name = args.next_arg()?;
+    let count = args.next_u64()? as usize;
+    let reverse = match args.next() {
+        None => false,
+        Some(arg) if arg.eq_ignore_ascii_case(b"REV") => true,
+        Some(_) => return Err(RedisError::Str("ERR syntax error")),
+    };
+
+    let index = INDEX.lock(ctx);
+    let mut iter = index.seek(op, &name);
+    let mut res = Vec::new();
+    while res.len() < count * 2 {
+        let entry = if reverse { iter.prev() } else { iter.next() };
+        let Some((name, value)) = entry else {
+            break;
+        };
+        res.push(RedisValue::StringBuffer(name));
+        res.push(RedisValue::Integer(*value));
+    }
+    Ok(RedisValue::Array(res))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "dict",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["INDEX.SET", index_set, "write", 0, 0, 0],
+        ["INDEX.GET", index_get, "readonly", 0, 0, 0],
+        ["INDEX.DEL", index_del, "write", 0, 0, 0],
+        ["INDEX.RANGE", index_range, "readonly", 0, 0, 0],
+    ],
+}
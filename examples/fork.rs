@@ -0,0 +1,74 @@
+use std::sync::Mutex;
+
+use redis_module::{
+    redis_module, ChildContext, Context, ForkResult, NextArg, RedisError, RedisResult, RedisString,
+    RedisValue,
+};
+
+/// The exit code of the last dump child, and whether it was killed by a
+/// signal.
+static LAST_DUMP: Mutex<Option<(i32, bool)>> = Mutex::new(None);
+
+fn dump_in_child(ctx: &Context, child: ChildContext, keys: &[RedisString], path: &str) -> ! {
+    let mut dump = Vec::new();
+    for (i, key) in keys.iter().enumerate() {
+        let key = ctx.open_key(key);
+        if let Ok(Some(value)) = key.read() {
+            dump.extend_from_slice(value);
+        }
+        dump.push(b'\n');
+        child.send_heartbeat((i + 1) as f64 / keys.len() as f64);
+    }
+    match std::fs::write(path, dump) {
+        Ok(()) => child.exit(0),
+        Err(_) => child.exit(1),
+    }
+}
+
+// FORK.DUMP path key [key ...]
+// Writes the string values of the keys to the given file, one per line, from
+// a fork child.
+fn fork_dump(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 3 {
+        return Err(RedisError::WrongArity);
+    }
+    let mut args = args.into_iter().skip(1);
+    let path = args.next_string()?;
+    let keys: Vec<RedisString> = args.collect();
+
+    *LAST_DUMP.lock().unwrap() = None;
+    match ctx.fork(|ctx, exit_code, by_signal| {
+        ctx.log_notice(&format!("Dump child exited with {exit_code}"));
+        *LAST_DUMP.lock().unwrap() = Some((exit_code, by_signal));
+    })? {
+        ForkResult::Parent(handle) => Ok(i64::from(handle.pid()).into()),
+        ForkResult::Child(child) => dump_in_child(ctx, child, &keys, &path),
+    }
+}
+
+// FORK.STATUS
+// Returns the exit code of the last dump child and whether it was killed by a
+// signal, or null if it is still running.
+fn fork_status(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let last_dump = LAST_DUMP.lock().unwrap();
+    Ok(match *last_dump {
+        Some((exit_code, by_signal)) => RedisValue::Array(vec![
+            i64::from(exit_code).into(),
+            RedisValue::Bool(by_signal),
+        ]),
+        None => RedisValue::Null,
+    })
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "fork",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["FORK.DUMP", fork_dump, "readonly", 2, -1, 1],
+        ["FORK.STATUS", fork_status, "readonly", 0, 0, 0],
+    ],
+}
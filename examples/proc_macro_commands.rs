@@ -127,6 +127,44 @@ fn num_keys(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::SimpleStringStatic("OK"))
 }
 
+#[command(
+    {
+        name: "docs_keys",
+        flags: [ReadOnly],
+        summary: "test command that documents its arguments",
+        since: "1.0.0",
+        history: [
+            { since: "1.1.0", changes: "Added the `NX` and `XX` options." },
+        ],
+        arity: -2,
+        key_spec: [
+            {
+                flags: [ReadOnly, Access],
+                begin_search: Index({ index : 1 }),
+                find_keys: Range({ last_key : 0, steps : 1, limit : 0 }),
+            }
+        ],
+        args: [
+            { name: "key", arg_type: Key, key_spec_index: 0 },
+            {
+                name: "condition",
+                arg_type: OneOf,
+                flags: [Optional],
+                since: "1.1.0",
+                subargs: [
+                    { name: "nx", arg_type: PureToken, token: "NX" },
+                    { name: "xx", arg_type: PureToken, token: "XX" },
+                ],
+            },
+            { name: "count", arg_type: Integer, token: "COUNT", flags: [Optional] },
+            { name: "member", arg_type: String, flags: [Multiple] },
+        ]
+    }
+)]
+fn docs_keys(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
 redis_module! {
     name: "server_events",
     version: 1,
@@ -0,0 +1,76 @@
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString};
+use redis_module_macros::command;
+
+// notify.publish channel message
+// Publishes the message to the channel, returns the number of clients which
+// received it.
+#[command(
+    {
+        name: "notify.publish",
+        flags: [PubSub, Fast, MayReplicate],
+        arity: 3,
+        key_spec: [],
+        channel_spec: [
+            {
+                flags: [Publish],
+                first: 1,
+                last: 1,
+                step: 1,
+            }
+        ]
+    }
+)]
+fn notify_publish(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let channel = args.next_arg()?;
+    let message = args.next_arg()?;
+    args.done()?;
+
+    Ok(ctx.publish(&channel, &message).into())
+}
+
+// notify.spublish channel message
+// Publishes the message to the shard channel, returns the number of clients
+// which received it. The channel is also declared as a key, so the command
+// is routed to the shard owning it in cluster mode.
+#[command(
+    {
+        name: "notify.spublish",
+        flags: [PubSub, Fast, MayReplicate],
+        arity: 3,
+        key_spec: [
+            {
+                notes: "the shard channel",
+                flags: [NotKey],
+                begin_search: Index({ index : 1 }),
+                find_keys: Range({ last_key : 0, steps : 1, limit : 0 }),
+            }
+        ],
+        channel_spec: [
+            {
+                flags: [Publish],
+                first: 1,
+                last: 1,
+                step: 1,
+            }
+        ]
+    }
+)]
+fn notify_spublish(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let channel = args.next_arg()?;
+    let message = args.next_arg()?;
+    args.done()?;
+
+    Ok(ctx.publish_shard(&channel, &message).into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "pubsub",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [],
+}
@@ -1,13 +1,23 @@
 use std::sync::atomic::{AtomicI64, Ordering};
 
-use redis_module::{
-    redis_module, server_events::FlushSubevent, Context, RedisResult, RedisString, RedisValue,
+use redis_module::key::RedisKey;
+use redis_module::server_events::{
+    ClientChangeSubevent, ClientInfo, FlushSubevent, KeySubevent, PersistenceSubevent, SwapDbInfo,
+};
+use redis_module::{redis_module, Context, KeyType, RedisResult, RedisString, RedisValue};
+use redis_module_macros::{
+    client_changed_event_handler, config_changed_event_handler, cron_event_handler,
+    flush_event_handler, key_event_handler, persistence_event_handler, swapdb_event_handler,
 };
-use redis_module_macros::{config_changed_event_handler, cron_event_handler, flush_event_handler};
 
 static NUM_FLUSHES: AtomicI64 = AtomicI64::new(0);
 static NUM_CRONS: AtomicI64 = AtomicI64::new(0);
 static NUM_MAX_MEMORY_CONFIGURATION_CHANGES: AtomicI64 = AtomicI64::new(0);
+static NUM_CONNECTED_CLIENTS: AtomicI64 = AtomicI64::new(0);
+static NUM_SWAPDBS: AtomicI64 = AtomicI64::new(0);
+static NUM_SAVES: AtomicI64 = AtomicI64::new(0);
+static NUM_DELETED_KEYS: AtomicI64 = AtomicI64::new(0);
+static NUM_OVERWRITTEN_KEYS: AtomicI64 = AtomicI64::new(0);
 
 #[flush_event_handler]
 fn flushed_event_handler(_ctx: &Context, flush_event: FlushSubevent) {
@@ -29,6 +39,37 @@ fn cron_event_handler(_ctx: &Context, _hz: u64) {
     NUM_CRONS.fetch_add(1, Ordering::SeqCst);
 }
 
+#[client_changed_event_handler]
+fn client_changed_event_handler(_ctx: &Context, event: ClientChangeSubevent, _client: &ClientInfo) {
+    match event {
+        ClientChangeSubevent::Connected => NUM_CONNECTED_CLIENTS.fetch_add(1, Ordering::SeqCst),
+        ClientChangeSubevent::Disconnected => NUM_CONNECTED_CLIENTS.fetch_sub(1, Ordering::SeqCst),
+    };
+}
+
+#[swapdb_event_handler]
+fn swapdb_event_handler(_ctx: &Context, _info: SwapDbInfo) {
+    NUM_SWAPDBS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[persistence_event_handler]
+fn persistence_event_handler(_ctx: &Context, event: PersistenceSubevent) {
+    if let PersistenceSubevent::Ended = event {
+        NUM_SAVES.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[key_event_handler]
+fn key_event_handler(_ctx: &Context, event: KeySubevent, _key_name: &RedisString, key: &RedisKey) {
+    match event {
+        KeySubevent::Deleted if key.key_type() == KeyType::String => {
+            NUM_DELETED_KEYS.fetch_add(1, Ordering::SeqCst)
+        }
+        KeySubevent::Overwritten => NUM_OVERWRITTEN_KEYS.fetch_add(1, Ordering::SeqCst),
+        _ => 0,
+    };
+}
+
 fn num_flushed(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::Integer(NUM_FLUSHES.load(Ordering::SeqCst)))
 }
@@ -43,6 +84,30 @@ fn num_maxmemory_changes(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult
     ))
 }
 
+fn num_connected_clients(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Integer(
+        NUM_CONNECTED_CLIENTS.load(Ordering::SeqCst),
+    ))
+}
+
+fn num_swapdbs(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Integer(NUM_SWAPDBS.load(Ordering::SeqCst)))
+}
+
+fn num_saves(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Integer(NUM_SAVES.load(Ordering::SeqCst)))
+}
+
+fn num_deleted_keys(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Integer(NUM_DELETED_KEYS.load(Ordering::SeqCst)))
+}
+
+fn num_overwritten_keys(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Integer(
+        NUM_OVERWRITTEN_KEYS.load(Ordering::SeqCst),
+    ))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -54,5 +119,10 @@ redis_module! {
         ["num_flushed", num_flushed, "readonly", 0, 0, 0],
         ["num_max_memory_changes", num_maxmemory_changes, "readonly", 0, 0, 0],
         ["num_crons", num_crons, "readonly", 0, 0, 0],
+        ["num_connected_clients", num_connected_clients, "readonly", 0, 0, 0],
+        ["num_swapdbs", num_swapdbs, "readonly", 0, 0, 0],
+        ["num_saves", num_saves, "readonly", 0, 0, 0],
+        ["num_deleted_keys", num_deleted_keys, "readonly", 0, 0, 0],
+        ["num_overwritten_keys", num_overwritten_keys, "readonly", 0, 0, 0],
     ],
 }
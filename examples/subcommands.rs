@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString, RedisValue};
+use redis_module_macros::command;
+
+/// The settings of the module, set with `admin.config set`.
+static SETTINGS: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+// admin.config get name
+// Returns the value of the setting, or null if it is not set.
+#[command(
+    {
+        name: "get",
+        parent: "admin.config",
+        flags: [ReadOnly, Fast],
+        summary: "Returns the value of a setting of the module",
+        acl_categories: ["fast"],
+        arity: 3,
+        key_spec: []
+    }
+)]
+fn config_get(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(2);
+    let name = args.next_string()?;
+    args.done()?;
+
+    let settings = SETTINGS.lock().unwrap();
+    Ok(settings
+        .as_ref()
+        .and_then(|settings| settings.get(&name))
+        .into())
+}
+
+// admin.config set name value
+// Sets the value of the setting.
+#[command(
+    {
+        name: "set",
+        parent: "admin.config",
+        flags: [Admin, DenyScript],
+        summary: "Sets a setting of the module",
+        acl_categories: ["admin", "dangerous"],
+        arity: 4,
+        key_spec: []
+    }
+)]
+fn config_set(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(2);
+    let name = args.next_string()?;
+    let value = args.next_string()?;
+    args.done()?;
+
+    SETTINGS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(name, value);
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+// admin.key exists key
+// Returns whether the key exists.
+#[command(
+    {
+        name: "exists",
+        parent: "admin.key",
+        flags: [ReadOnly, Fast],
+        arity: 3,
+        key_spec: [
+            {
+                notes: "the key to check",
+                flags: [ReadOnly],
+                begin_search: Index({ index : 2 }),
+                find_keys: Range({ last_key : 0, steps : 1, limit : 0 }),
+            }
+        ]
+    }
+)]
+fn key_exists(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(2);
+    let key_name = args.next_arg()?;
+    args.done()?;
+
+    Ok((!ctx.open_key(&key_name).is_null()).into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "subcommands",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [],
+}
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use redis_module::aof::AofEmitter;
+use redis_module::digest::Digest;
+use redis_module::error::Error;
+use redis_module::key_opt::KeyOptCtx;
+use redis_module::native_types::RedisDataType;
+use redis_module::raw::ModuleOptions;
+use redis_module::rdb::{RdbReader, RdbWriter};
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue, Status,
+};
+use redis_module_macros::redis_data_type;
+
+/// A list of strings, stored in a custom data type.
+#[derive(Debug, Clone)]
+struct StringList {
+    items: Vec<String>,
+}
+
+#[redis_data_type({ name: "strlist01", encoding_version: 1 })]
+impl RedisDataType for StringList {
+    fn rdb_load(rdb: &mut RdbReader, _encver: i32) -> Result<Self, Error> {
+        let len = rdb.read_unsigned()?;
+        let items = (0..len)
+            .map(|_| Ok(rdb.read_string_buffer()?.to_string()?))
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { items })
+    }
+
+    fn rdb_save(&self, rdb: &mut RdbWriter) {
+        rdb.write_unsigned(self.items.len() as u64);
+        self.items.iter().for_each(|item| rdb.write_str(item));
+    }
+
+    fn aof_rewrite(&self, aof: &mut AofEmitter, key: &RedisString) {
+        let mut args: Vec<&[u8]> = vec![key.as_slice()];
+        args.extend(self.items.iter().map(|item| item.as_bytes()));
+        aof.emit("STRLIST.PUSH", args.as_slice());
+    }
+
+    fn mem_usage(&self, _ctx: &KeyOptCtx, _sample_size: usize) -> usize {
+        std::mem::size_of::<Self>() + self.items.iter().map(String::capacity).sum::<usize>()
+    }
+
+    fn digest(&self, digest: &mut Digest) {
+        digest.add_ordered(&self.items);
+    }
+
+    fn free_effort(&self, _ctx: &KeyOptCtx) -> usize {
+        self.items.len()
+    }
+
+    fn copy(&self, _ctx: &KeyOptCtx) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+/// Named counters, persisted with serde.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Counters {
+    counts: BTreeMap<String, i64>,
+    /// The total of all the increments, added in encoding version 2.
+    total: i64,
+}
+
+#[redis_data_type({ name: "counters1", encoding_version: 2 })]
+impl RedisDataType for Counters {
+    fn rdb_load(rdb: &mut RdbReader, _encver: i32) -> Result<Self, Error> {
+        let counts: BTreeMap<String, i64> = rdb.deserialize()?;
+        let total = rdb.deserialize_since(2, counts.values().sum())?;
+        Ok(Self { counts, total })
+    }
+
+    fn rdb_save(&self, rdb: &mut RdbWriter) {
+        // Serializing maps of strings and integers cannot fail.
+        rdb.serialize(&self.counts).unwrap();
+        rdb.serialize(&self.total).unwrap();
+    }
+
+    fn aof_rewrite(&self, aof: &mut AofEmitter, key: &RedisString) {
+        for (name, count) in &self.counts {
+            aof.emit_args("COUNTERS.INCR", &[key.into(), name.into(), (*count).into()]);
+        }
+    }
+
+    fn digest(&self, digest: &mut Digest) {
+        digest.add_unordered_with(&self.counts, |digest, (name, count)| {
+            digest.add_string_buffer(name.as_bytes());
+            digest.add_long_long(*count);
+        });
+    }
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    // Invalid input of STRLIST.IMPORT fails the load instead of crashing
+    // the server.
+    ctx.set_module_options(ModuleOptions::HANDLE_IO_ERRORS);
+    Status::Ok
+}
+
+// STRLIST.PUSH key item [item ...]
+// Appends the items to the list stored at key.
+fn strlist_push(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 3 {
+        return Err(RedisError::WrongArity);
+    }
+    let mut args = args.into_iter().skip(1);
+    let key = ctx.open_key_writable(&args.next_arg()?);
+    let items = args.map(|item| item.to_string_lossy());
+
+    let len = match key.get_typed_value::<StringList>()? {
+        Some(list) => {
+            list.items.extend(items);
+            list.items.len()
+        }
+        None => {
+            let list = StringList {
+                items: items.collect(),
+            };
+            let len = list.items.len();
+            key.set_typed_value(list)?;
+            len
+        }
+    };
+    Ok(len.into())
+}
+
+// STRLIST.RANGE key
+// Returns the items of the list stored at key.
+fn strlist_range(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = ctx.open_key(&args.next_arg()?);
+    args.done()?;
+
+    let items = key
+        .get_typed_value::<StringList>()?
+        .map_or_else(Vec::new, |list| list.items.clone());
+    Ok(items.into())
+}
+
+// STRLIST.EXPORT key
+// Returns the list stored at key serialized to a string, or null.
+fn strlist_export(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = ctx.open_key(&args.next_arg()?);
+    args.done()?;
+
+    match key.get_typed_value::<StringList>()? {
+        Some(list) => Ok(StringList::redis_type().serialize_value(ctx, list)?.into()),
+        None => Ok(RedisValue::Null),
+    }
+}
+
+// STRLIST.IMPORT key data
+// Replaces the value at key with the list serialized by STRLIST.EXPORT.
+fn strlist_import(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = ctx.open_key_writable(&args.next_arg()?);
+    let data = args.next_arg()?;
+    args.done()?;
+
+    let list = StringList::redis_type()
+        .deserialize_value::<StringList>(&data)
+        .map_err(|_| RedisError::Str("ERR invalid serialized list"))?;
+    key.set_typed_value(*list)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+// COUNTERS.INCR key name [increment]
+// Increments the named counter stored at key by increment, 1 by default,
+// returns its new value and the total of all the increments.
+fn counters_incr(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = ctx.open_key_writable(&args.next_arg()?);
+    let name = args.next_string()?;
+    let increment = if args.len() > 0 { args.next_i64()? } else { 1 };
+    args.done()?;
+
+    if key.get_typed_value::<Counters>()?.is_none() {
+        key.set_typed_value(Counters::default())?;
+    }
+    let counters = key
+        .get_typed_value::<Counters>()?
+        .ok_or(RedisError::Str("ERR failed creating the counters"))?;
+    let count = counters.counts.entry(name).or_default();
+    *count += increment;
+    counters.total += increment;
+    Ok(vec![*count, counters.total].into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "typed_data_type",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [
+        ["STRLIST.PUSH", strlist_push, "write deny-oom", 1, 1, 1],
+        ["STRLIST.RANGE", strlist_range, "readonly", 1, 1, 1],
+        ["STRLIST.EXPORT", strlist_export, "readonly", 1, 1, 1],
+        ["STRLIST.IMPORT", strlist_import, "write deny-oom", 1, 1, 1],
+        ["COUNTERS.INCR", counters_incr, "write deny-oom", 1, 1, 1],
+    ],
+}
@@ -0,0 +1,146 @@
+use redis_module::zset::{LexBound, ScoreBound, ZaddFlags, ZsetElement};
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+// LEADERBOARD.ADD key score member
+// Sets the score of a member, returns 1 if the member is new and 0 otherwise.
+fn leaderboard_add(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let score = args.next_f64()?;
+    let member = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key_name);
+    let flags = key.zset_add(score, &member, ZaddFlags::empty())?;
+    Ok(RedisValue::Integer(flags.contains(ZaddFlags::ADDED).into()))
+}
+
+// LEADERBOARD.INCR key increment member
+// Increments the score of an existing member and returns the new score.
+fn leaderboard_incr(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let increment = args.next_f64()?;
+    let member = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key_name);
+    Ok(key
+        .zset_incrby(increment, &member, ZaddFlags::XX)?
+        .map_or(RedisValue::Null, RedisValue::Float))
+}
+
+// LEADERBOARD.REM key member
+fn leaderboard_rem(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let member = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key_name);
+    Ok(RedisValue::Integer(key.zset_rem(&member)?.into()))
+}
+
+// LEADERBOARD.SCORE key member
+fn leaderboard_score(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let member = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    Ok(key
+        .zset_score(&member)?
+        .map_or(RedisValue::Null, RedisValue::Float))
+}
+
+fn parse_score_bound(arg: &RedisString) -> Result<ScoreBound, RedisError> {
+    let arg = arg.try_as_str()?;
+    let bound = match arg {
+        "-inf" | "+inf" => ScoreBound::Unbounded,
+        _ => match arg.strip_prefix('(') {
+            Some(score) => ScoreBound::Exclusive(score.parse()?),
+            None => ScoreBound::Inclusive(arg.parse()?),
+        },
+    };
+    Ok(bound)
+}
+
+fn parse_lex_bound(arg: &RedisString) -> Result<LexBound<'_>, RedisError> {
+    match arg.as_slice() {
+        b"-" | b"+" => Ok(LexBound::Unbounded),
+        [b'[', value @ ..] => Ok(LexBound::Inclusive(value)),
+        [b'(', value @ ..] => Ok(LexBound::Exclusive(value)),
+        _ => Err(RedisError::Str(
+            "ERR min or max not valid string range item",
+        )),
+    }
+}
+
+fn reply_elements(elements: impl Iterator<Item = ZsetElement>) -> RedisValue {
+    RedisValue::Array(
+        elements
+            .flat_map(|e| {
+                [
+                    RedisValue::BulkRedisString(e.element),
+                    RedisValue::Float(e.score),
+                ]
+            })
+            .collect(),
+    )
+}
+
+// LEADERBOARD.RANGE key min max [REV]
+// Returns members and scores between min and max, using the ZRANGEBYSCORE
+// syntax for the bounds.
+fn leaderboard_range(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let min = parse_score_bound(&args.next_arg()?)?;
+    let max = parse_score_bound(&args.next_arg()?)?;
+    let reverse = args.next().is_some();
+
+    let key = ctx.open_key(&key_name);
+    if key.is_null() {
+        return Ok(RedisValue::Array(vec![]));
+    }
+    let iter = key.get_zset_score_range_iterator(min, max, reverse)?;
+    Ok(reply_elements(iter))
+}
+
+// LEADERBOARD.LEXRANGE key min max [REV]
+// Same as LEADERBOARD.RANGE but using the ZRANGEBYLEX syntax for the bounds.
+fn leaderboard_lexrange(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let min = args.next_arg()?;
+    let max = args.next_arg()?;
+    let reverse = args.next().is_some();
+
+    let key = ctx.open_key(&key_name);
+    if key.is_null() {
+        return Ok(RedisValue::Array(vec![]));
+    }
+    let iter =
+        key.get_zset_lex_range_iterator(parse_lex_bound(&min)?, parse_lex_bound(&max)?, reverse)?;
+    Ok(reply_elements(iter))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "zset",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["LEADERBOARD.ADD", leaderboard_add, "write fast deny-oom", 1, 1, 1],
+        ["LEADERBOARD.INCR", leaderboard_incr, "write fast deny-oom", 1, 1, 1],
+        ["LEADERBOARD.REM", leaderboard_rem, "write fast", 1, 1, 1],
+        ["LEADERBOARD.SCORE", leaderboard_score, "readonly fast", 1, 1, 1],
+        ["LEADERBOARD.RANGE", leaderboard_range, "readonly", 1, 1, 1],
+        ["LEADERBOARD.LEXRANGE", leaderboard_lexrange, "readonly", 1, 1, 1],
+    ],
+}
@@ -21,6 +21,19 @@ lazy_static::lazy_static! {
         ("RedisModule_ACLAddLogEntryByUserName".to_string(), 70200),
         ("RedisModule_GetCommand".to_string(), 70000),
         ("RedisModule_SetCommandInfo".to_string(), 70000),
+        ("RedisModule_GetKeyNameFromDefragCtx".to_string(), 70000),
+        ("RedisModule_GetDbIdFromDefragCtx".to_string(), 70000),
+        ("RedisModule_GetDbIdFromIO".to_string(), 70000),
+        ("RedisModule_GetKeyNameFromDigest".to_string(), 70000),
+        ("RedisModule_GetDbIdFromDigest".to_string(), 70000),
+        ("RedisModule_LoadDataTypeFromStringEncver".to_string(), 70000),
+        ("RedisModule_SetModuleUserACLString".to_string(), 70200),
+        ("RedisModule_GetModuleUserACLString".to_string(), 70200),
+        ("RedisModule_PublishMessageShard".to_string(), 70000),
+        ("RedisModule_CreateSubcommand".to_string(), 70000),
+        // Added in Redis 7.0.3, so a 7.0 module must check them at runtime.
+        ("RedisModule_GetClientNameById".to_string(), 70200),
+        ("RedisModule_SetClientNameById".to_string(), 70200),
 
     ]);
 
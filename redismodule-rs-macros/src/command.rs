@@ -163,6 +163,32 @@ impl From<&RedisCommandKeySpecFlags> for &'static str {
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub enum RedisCommandChannelFlags {
+    /// The channel is a pattern, as for `PSUBSCRIBE`.
+    Pattern,
+
+    /// The command publishes to the channel.
+    Publish,
+
+    /// The command subscribes to the channel.
+    Subscribe,
+
+    /// The command unsubscribes from the channel.
+    Unsubscribe,
+}
+
+impl From<&RedisCommandChannelFlags> for &'static str {
+    fn from(value: &RedisCommandChannelFlags) -> Self {
+        match value {
+            RedisCommandChannelFlags::Pattern => "PATTERN",
+            RedisCommandChannelFlags::Publish => "PUBLISH",
+            RedisCommandChannelFlags::Subscribe => "SUBSCRIBE",
+            RedisCommandChannelFlags::Unsubscribe => "UNSUBSCRIBE",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct FindKeysRange {
     last_key: i32,
@@ -208,16 +234,101 @@ pub struct KeySpecArg {
     find_keys: FindKeys,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ChannelSpecArg {
+    flags: Vec<RedisCommandChannelFlags>,
+    first: i32,
+    last: i32,
+    step: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub enum RedisCommandArgType {
+    String,
+    Integer,
+    Double,
+    Key,
+    Pattern,
+    UnixTime,
+    PureToken,
+    OneOf,
+    Block,
+}
+
+impl From<&RedisCommandArgType> for &'static str {
+    fn from(value: &RedisCommandArgType) -> Self {
+        match value {
+            RedisCommandArgType::String => "String",
+            RedisCommandArgType::Integer => "Integer",
+            RedisCommandArgType::Double => "Double",
+            RedisCommandArgType::Key => "Key",
+            RedisCommandArgType::Pattern => "Pattern",
+            RedisCommandArgType::UnixTime => "UnixTime",
+            RedisCommandArgType::PureToken => "PureToken",
+            RedisCommandArgType::OneOf => "OneOf",
+            RedisCommandArgType::Block => "Block",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub enum RedisCommandArgFlags {
+    /// The argument is optional.
+    Optional,
+
+    /// The argument may repeat itself.
+    Multiple,
+
+    /// The argument may repeat itself, and so does its token.
+    MultipleToken,
+}
+
+impl From<&RedisCommandArgFlags> for &'static str {
+    fn from(value: &RedisCommandArgFlags) -> Self {
+        match value {
+            RedisCommandArgFlags::Optional => "OPTIONAL",
+            RedisCommandArgFlags::Multiple => "MULTIPLE",
+            RedisCommandArgFlags::MultipleToken => "MULTIPLE_TOKEN",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommandArgument {
+    name: String,
+    arg_type: RedisCommandArgType,
+    key_spec_index: Option<i32>,
+    token: Option<String>,
+    summary: Option<String>,
+    since: Option<String>,
+    flags: Option<Vec<RedisCommandArgFlags>>,
+    deprecated_since: Option<String>,
+    subargs: Option<Vec<CommandArgument>>,
+    display_text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryEntryArg {
+    since: String,
+    changes: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct Args {
     name: Option<String>,
+    parent: Option<String>,
     flags: Vec<RedisCommandFlags>,
     summary: Option<String>,
     complexity: Option<String>,
     since: Option<String>,
+    history: Option<Vec<HistoryEntryArg>>,
     tips: Option<String>,
+    acl_categories: Option<Vec<String>>,
     arity: i64,
     key_spec: Vec<KeySpecArg>,
+    channel_spec: Option<Vec<ChannelSpecArg>>,
+    args: Option<Vec<CommandArgument>>,
+    args_type: Option<String>,
 }
 
 impl Parse for Args {
@@ -231,6 +342,42 @@ fn to_token_stream(s: Option<String>) -> proc_macro2::TokenStream {
         .unwrap_or(quote! {None})
 }
 
+fn to_option_token_stream<T: quote::ToTokens>(v: Option<T>) -> proc_macro2::TokenStream {
+    v.map(|v| quote! {Some(#v)}).unwrap_or(quote! {None})
+}
+
+fn command_arg_token_stream(arg: &CommandArgument) -> proc_macro2::TokenStream {
+    let name = arg.name.as_str();
+    let arg_type = Ident::new((&arg.arg_type).into(), proc_macro2::Span::call_site());
+    let key_spec_index = to_option_token_stream(arg.key_spec_index);
+    let token = to_token_stream(arg.token.clone());
+    let summary = to_token_stream(arg.summary.clone());
+    let since = to_token_stream(arg.since.clone());
+    let flags: Vec<&'static str> = arg.flags.iter().flatten().map(|v| v.into()).collect();
+    let deprecated_since = to_token_stream(arg.deprecated_since.clone());
+    let subargs: Vec<_> = arg
+        .subargs
+        .iter()
+        .flatten()
+        .map(command_arg_token_stream)
+        .collect();
+    let display_text = to_token_stream(arg.display_text.clone());
+    quote! {
+        redis_module::commands::CommandArg::new(
+            #name.to_owned(),
+            redis_module::commands::CommandArgType::#arg_type,
+            #key_spec_index,
+            #token,
+            #summary,
+            #since,
+            redis_module::commands::CommandArgFlags::empty() #(| redis_module::commands::CommandArgFlags::try_from(#flags)?)*,
+            #deprecated_since,
+            vec![#(#subargs, )*],
+            #display_text,
+        )
+    }
+}
+
 pub(crate) fn redis_command(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_macro_input!(attr as Args);
     let func: ItemFn = match syn::parse(item) {
@@ -253,8 +400,16 @@ pub(crate) fn redis_command(attr: TokenStream, item: TokenStream) -> TokenStream
     let name_literal = args
         .name
         .unwrap_or_else(|| original_function_name.to_string());
-    let flags_str = args
-        .flags
+    let channel_spec = args.channel_spec.unwrap_or_default();
+    let mut flags = args.flags;
+    if !channel_spec.is_empty()
+        && !flags
+            .iter()
+            .any(|v| matches!(v, RedisCommandFlags::GetchannelsApi))
+    {
+        flags.push(RedisCommandFlags::GetchannelsApi);
+    }
+    let flags_str = flags
         .into_iter()
         .fold(String::new(), |s, v| {
             format!("{} {}", s, Into::<&'static str>::into(&v))
@@ -262,10 +417,43 @@ pub(crate) fn redis_command(attr: TokenStream, item: TokenStream) -> TokenStream
         .trim()
         .to_owned();
     let flags_literal = quote!(#flags_str);
+    let parent_literal = to_token_stream(args.parent);
     let summary_literal = to_token_stream(args.summary);
     let complexity_literal = to_token_stream(args.complexity);
     let since_literal = to_token_stream(args.since);
+    let history: Vec<_> = args
+        .history
+        .iter()
+        .flatten()
+        .map(|v| {
+            let since = v.since.as_str();
+            let changes = v.changes.as_str();
+            quote! {
+                redis_module::commands::CommandHistoryEntry::new(#since.to_owned(), #changes.to_owned())
+            }
+        })
+        .collect();
+    let command_args: Vec<_> = args
+        .args
+        .iter()
+        .flatten()
+        .map(command_arg_token_stream)
+        .collect();
+    let command_args = match args.args_type {
+        Some(_) if args.args.is_some() => {
+            return quote! {compile_error!("args and args_type can not be used together.")}.into()
+        }
+        Some(args_type) => {
+            let args_type: syn::Path = match syn::parse_str(&args_type) {
+                Ok(res) => res,
+                Err(e) => return e.to_compile_error().into(),
+            };
+            quote! {<#args_type as redis_module::RedisArgs>::command_args()}
+        }
+        None => quote! {vec![#(#command_args, )*]},
+    };
     let tips_literal = to_token_stream(args.tips);
+    let acl_categories_literal = to_token_stream(args.acl_categories.map(|v| v.join(" ")));
     let arity_literal = args.arity;
     let key_spec_notes: Vec<_> = args
         .key_spec
@@ -332,6 +520,39 @@ pub(crate) fn redis_command(attr: TokenStream, item: TokenStream) -> TokenStream
         })
         .collect();
 
+    let channel_specs: Vec<_> = channel_spec
+        .iter()
+        .map(|v| {
+            let flags: Vec<_> = v
+                .flags
+                .iter()
+                .map(|v| Ident::new(v.into(), original_function_name.span()))
+                .collect();
+            let first = v.first;
+            let last = v.last;
+            let step = v.step;
+            quote! {
+                redis_module::commands::ChannelSpec::new(
+                    redis_module::AclChannelPermissions::empty() #(| redis_module::AclChannelPermissions::#flags)*,
+                    #first,
+                    #last,
+                    #step,
+                )
+            }
+        })
+        .collect();
+
+    let channels_position_request = if channel_specs.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            if context.is_channels_position_request() {
+                #(#channel_specs.report_channels(&context, argc);)*
+                return redis_module::raw::Status::Ok as i32;
+            }
+        }
+    };
+
     let gen = quote! {
         #func
 
@@ -341,6 +562,7 @@ pub(crate) fn redis_command(attr: TokenStream, item: TokenStream) -> TokenStream
             argc: i32,
         ) -> i32 {
             let context = redis_module::Context::new(ctx);
+            #channels_position_request
 
             let args = redis_module::decode_args(ctx, argv, argc);
             let response = #original_function_name(&context, args);
@@ -361,13 +583,17 @@ pub(crate) fn redis_command(attr: TokenStream, item: TokenStream) -> TokenStream
             ];
             Ok(redis_module::commands::CommandInfo::new(
                 #name_literal.to_owned(),
+                #parent_literal,
                 Some(#flags_literal.to_owned()),
                 #summary_literal,
                 #complexity_literal,
                 #since_literal,
+                vec![#(#history, )*],
                 #tips_literal,
+                #acl_categories_literal,
                 #arity_literal,
                 key_spec,
+                #command_args,
                 #c_function_name,
             ))
         }
@@ -0,0 +1,117 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span};
+use quote::quote;
+use serde::Deserialize;
+use serde_syn::{config, from_stream};
+use syn::{
+    parse,
+    parse::{Parse, ParseStream},
+    parse_macro_input, ImplItem, ItemImpl,
+};
+
+/// The optional methods of `RedisDataType`, with the callback flag each one
+/// sets.
+const OPTIONAL_METHODS: &[(&str, &str)] = &[
+    ("aof_rewrite", "AOF_REWRITE"),
+    ("mem_usage", "MEM_USAGE"),
+    ("digest", "DIGEST"),
+    ("free_effort", "FREE_EFFORT"),
+    ("unlink", "UNLINK"),
+    ("copy", "COPY"),
+    ("defrag", "DEFRAG"),
+    ("aux_load", "AUX"),
+    ("aux_save", "AUX"),
+];
+
+#[derive(Debug, Deserialize)]
+struct Args {
+    name: String,
+    encoding_version: i32,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        from_stream(config::JSONY, input)
+    }
+}
+
+pub(crate) fn redis_data_type(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as Args);
+    let mut ast: ItemImpl = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    if args.name.len() != 9 {
+        return syn::Error::new(
+            Span::call_site(),
+            format!(
+                "Redis requires the length of native type names to be exactly 9 characters, name is: '{}'",
+                args.name
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if ast.trait_.is_none() {
+        return syn::Error::new(
+            Span::call_site(),
+            "redis_data_type must be set on an `impl RedisDataType for ...` block",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let mut callbacks: Vec<&str> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Method(method) => Some(method.sig.ident.to_string()),
+            _ => None,
+        })
+        .filter_map(|method| {
+            OPTIONAL_METHODS
+                .iter()
+                .find(|(name, _)| *name == method)
+                .map(|(_, flag)| *flag)
+        })
+        .collect();
+    callbacks.sort_unstable();
+    callbacks.dedup();
+    let callbacks = callbacks
+        .into_iter()
+        .map(|flag| Ident::new(flag, Span::call_site()));
+
+    let self_ty = ast.self_ty.clone();
+    let name = args.name;
+    let encoding_version = args.encoding_version;
+
+    let generated: ItemImpl = syn::parse_quote! {
+        impl X {
+            const NAME: &'static str = #name;
+            const ENCODING_VERSION: i32 = #encoding_version;
+            const CALLBACKS: redis_module::native_types::DataTypeCallbacks =
+                redis_module::native_types::DataTypeCallbacks::empty()
+                    #(.union(redis_module::native_types::DataTypeCallbacks::#callbacks))*;
+
+            fn redis_type() -> &'static redis_module::native_types::RedisType {
+                static REDIS_TYPE: redis_module::native_types::RedisType =
+                    redis_module::native_types::RedisType::of::<#self_ty>();
+                &REDIS_TYPE
+            }
+        }
+    };
+    ast.items.splice(0..0, generated.items);
+
+    let gen = quote! {
+        #ast
+
+        const _: () = {
+            #[linkme::distributed_slice(redis_module::native_types::DATA_TYPES_LIST)]
+            static DATA_TYPE: fn() -> &'static redis_module::native_types::RedisType =
+                <#self_ty as redis_module::native_types::RedisDataType>::redis_type;
+        };
+    };
+    gen.into()
+}
@@ -3,17 +3,27 @@ use quote::quote;
 use syn::ItemFn;
 
 mod command;
+mod data_type;
 mod info_section;
+mod redis_args;
 mod redis_value;
 
 /// This proc macro allow to specify that the follow function is a Redis command.
 /// The macro accept the following arguments that discribe the command properties:
 /// * name (optional) - The command name. in case not given, the function name will be taken.
+/// * parent (optional) - The name of the parent command, in which case the command is registered as a subcommand
+///   of it, e.g. `GET` of `MYMOD.CONFIG`. The parent is registered as a container command, which only has
+///   subcommands, so it must not be a command of the module itself. The arity and the positions of the keys and
+///   channels of a subcommand include the name of the parent command.
 /// * flags - An array of `RedisCommandFlags`.
 /// * summary (optional) - Command summary
 /// * complexity (optional) - Command compexity
 /// * since (optional) - At which module version the command was first introduce
+/// * history (optional) - A list of the changes of the command, shown by `COMMAND DOCS`, each with `since`, the
+///   module version of the change, and `changes`, its description.
 /// * tips (optional) - Command tips for proxy, for more information please refer to https://redis.io/topics/command-tips
+/// * acl_categories (optional) - A list of ACL categories of the command, e.g. `["read", "fast"]`, which may include
+///   the categories added by the module, see `acl_categories` of `redis_module!`.
 /// * arity - Number of arguments, including the command name itself. A positive number specifies an exact number of arguments and a negative number
 ///   specifies a minimum number of arguments.
 /// * key_spec - A list of specs representing how to find the keys that the command might touch. the following options are available:
@@ -55,6 +65,36 @@ mod redis_value;
 ///            which case it should be set to `keynumidx + 1`.)
 ///          * keystep - How many arguments should we skip after finding a
 ///            key, in order to find the next one?
+/// * channel_spec (optional) - A list of specs representing which arguments are pubsub channels, used to check
+///   the ACL channel permissions of the user. The `GetchannelsApi` flag is added to the command when given.
+///   Sharded channels should also be declared in `key_spec` with the `NotKey` flag, so the command is routed
+///   to the shard owning the channel in cluster mode. Each spec has the following options:
+///    * flags - List of flags representing how the channels are used, the following options are available:
+///       * Pattern - The channel is a pattern, as for `PSUBSCRIBE`.
+///       * Publish - The command publishes to the channel.
+///       * Subscribe - The command subscribes to the channel.
+///       * Unsubscribe - The command unsubscribes from the channel.
+///    * first - Index of the first channel.
+///    * last - Index of the last channel. Can be negative, in which case -1 indicates the last argument,
+///      -2 one before the last and so on.
+///    * step - How many arguments should we skip after finding a channel, in order to find the next one.
+/// * args (optional) - A list of the arguments of the command, shown by `COMMAND DOCS` and used by clients such
+///   as `redis-cli` for hints. Each argument has the following options:
+///    * name - Name of the argument.
+///    * arg_type - Type of the argument, one of `String`, `Integer`, `Double`, `Key`, `Pattern`, `UnixTime`,
+///      `PureToken`, `OneOf` and `Block`.
+///    * key_spec_index (optional) - For `Key` arguments, the index of the key spec which covers the argument.
+///    * token (optional) - The token preceding the argument, e.g. `COUNT`.
+///    * summary (optional) - A short description of the argument.
+///    * since (optional) - The first module version which included the argument.
+///    * flags (optional) - List of flags, the following options are available:
+///       * Optional - The argument is optional.
+///       * Multiple - The argument may repeat itself.
+///       * MultipleToken - The argument may repeat itself, and so does its token.
+///    * deprecated_since (optional) - The first module version which deprecated the argument.
+///    * subargs (optional) - The arguments of a `OneOf` or a `Block` argument.
+///    * display_text (optional) - The text shown instead of the name of the argument.
+/// * args_type (optional) - The name of a type deriving `RedisArgs`, whose arguments are set instead of `args`.
 ///
 /// Example:
 /// The following example will register a command called `foo`.
@@ -85,6 +125,30 @@ pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
     command::redis_command(attr, item)
 }
 
+/// Proc macro which is set on an `impl RedisDataType for ...` block to
+/// register the custom data type, which is then created when the module is
+/// loaded. The macro accepts the following arguments:
+/// * name - The name of the type, exactly 9 characters long.
+/// * encoding_version - The version of the RDB encoding of the type.
+///
+/// The macro sets the optional callbacks of the type according to the
+/// methods implemented in the block.
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[redis_data_type({ name: "mytype123", encoding_version: 1 })]
+/// impl RedisDataType for MyType {
+///     fn rdb_load(rdb: &mut RdbReader, _encver: i32) -> Result<Self, Error> { ... }
+///     fn rdb_save(&self, rdb: &mut RdbWriter) { ... }
+///     fn mem_usage(&self, ctx: &KeyOptCtx, sample_size: usize) -> usize { ... }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn redis_data_type(attr: TokenStream, item: TokenStream) -> TokenStream {
+    data_type::redis_data_type(attr, item)
+}
+
 /// Proc macro which is set on a function that need to be called whenever the server role changes.
 /// The function must accept a [Context] and [ServerRole].
 ///
@@ -218,6 +282,249 @@ pub fn cron_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream
     gen.into()
 }
 
+/// Proc macro which is set on a function that need to be called whenever a client connects or disconnects.
+/// The function must accept a [Context], a [ClientChangeSubevent] and the [ClientInfo] of the client.
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[client_changed_event_handler]
+/// fn client_changed_event_handler(ctx: &Context, values: ClientChangeSubevent, client: &ClientInfo) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn client_changed_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::CLIENT_CHANGED_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
+/// Proc macro which is set on a function that need to be called whenever the server is shutting down.
+/// The function must accept a [Context].
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[shutdown_event_handler]
+/// fn shutdown_event_handler(ctx: &Context) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn shutdown_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::SHUTDOWN_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
+/// Proc macro which is set on a function that need to be called whenever a replica goes online or offline.
+/// The function must accept a [Context] and [ReplicaChangeSubevent].
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[replica_changed_event_handler]
+/// fn replica_changed_event_handler(ctx: &Context, values: ReplicaChangeSubevent) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn replica_changed_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::REPLICA_CHANGED_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
+/// Proc macro which is set on a function that need to be called whenever an RDB or AOF persistence starts, ends or fails.
+/// The function must accept a [Context] and [PersistenceSubevent].
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[persistence_event_handler]
+/// fn persistence_event_handler(ctx: &Context, values: PersistenceSubevent) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn persistence_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::PERSISTENCE_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
+/// Proc macro which is set on a function that need to be called whenever the link of a replica with its master goes up or down.
+/// The function must accept a [Context] and [MasterLinkChangeSubevent].
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[master_link_changed_event_handler]
+/// fn master_link_changed_event_handler(ctx: &Context, values: MasterLinkChangeSubevent) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn master_link_changed_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::MASTER_LINK_CHANGED_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
+/// Proc macro which is set on a function that need to be called whenever two databases are swapped with `SWAPDB`.
+/// The function must accept a [Context] and [SwapDbInfo].
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[swapdb_event_handler]
+/// fn swapdb_event_handler(ctx: &Context, values: SwapDbInfo) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn swapdb_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::SWAPDB_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
+/// Proc macro which is set on a function that need to be called whenever a replica backs up its data before a full sync (Redis 6.2 only).
+/// The function must accept a [Context] and [ReplBackupSubevent].
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[repl_backup_event_handler]
+/// fn repl_backup_event_handler(ctx: &Context, values: ReplBackupSubevent) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn repl_backup_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::REPL_BACKUP_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
+/// Proc macro which is set on a function that need to be called whenever a replica loads the data of a full sync asynchronously.
+/// The function must accept a [Context] and [ReplAsyncLoadSubevent].
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[repl_async_load_event_handler]
+/// fn repl_async_load_event_handler(ctx: &Context, values: ReplAsyncLoadSubevent) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn repl_async_load_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::REPL_ASYNC_LOAD_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
+/// Proc macro which is set on a function that need to be called whenever a fork child is born or dies.
+/// The function must accept a [Context] and [ForkChildSubevent].
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[fork_child_event_handler]
+/// fn fork_child_event_handler(ctx: &Context, values: ForkChildSubevent) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn fork_child_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::FORK_CHILD_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
+/// Proc macro which is set on a function that need to be called whenever the event loop goes to sleep or wakes up.
+/// The function must accept a [Context] and [EventLoopSubevent].
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[eventloop_event_handler]
+/// fn eventloop_event_handler(ctx: &Context, values: EventLoopSubevent) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn eventloop_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::EVENTLOOP_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
+/// Proc macro which is set on a function that need to be called whenever a key is deleted, expired, evicted or overwritten.
+/// The function must accept a [Context], a [KeySubevent], the key name and the [RedisKey] itself,
+/// which can be read before it is removed.
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[key_event_handler]
+/// fn key_event_handler(ctx: &Context, values: KeySubevent, key_name: &RedisString, key: &RedisKey) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn key_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::KEY_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
 /// The macro auto generate a [From] implementation that can convert the struct into [RedisValue].
 ///
 /// Example:
@@ -362,6 +669,76 @@ pub fn redis_value(item: TokenStream) -> TokenStream {
     redis_value::redis_value(item)
 }
 
+/// The macro auto generate a `redis_module::RedisArgs` implementation
+/// which parses the arguments of a command into the struct, and describes
+/// them for `COMMAND DOCS`. The fields are parsed in order:
+/// * A field of a type implementing `redis_module::FromRedisArg` (e.g.
+///   [`i64`], [`String`] or `RedisString`) is a required argument.
+/// * An [`Option`] field is an optional argument and a [`Vec`] field an
+///   argument which repeats itself, at least once. Both end at the first
+///   argument which is a token of one of the following fields. Unless their
+///   values are tokens, e.g. of an enum deriving `RedisArgs`, they must not
+///   be followed by fields without a token, which is checked at compile
+///   time.
+/// * A field with a `token` is a `TOKEN value` pair, a [`bool`] field with
+///   a `token` is a token without a value. Consecutive fields with tokens
+///   may appear in any order, [`Option`], [`Vec`] and [`bool`] ones are
+///   optional and a [`Vec`] one may repeat itself with its token.
+/// * A field with `block: true` is a group of arguments parsed by the
+///   `redis_module::RedisArgs` implementation of its type.
+/// * A field with a `key_spec_index` is a key, covered by the key spec of
+///   the given index.
+///
+/// Missing arguments, including required `TOKEN value` pairs, result in a
+/// `WrongArity` error, unexpected arguments, duplicated tokens and tokens
+/// without a value in an `ERR syntax error` error.
+///
+/// On an enum of unit variants, the macro auto generate a
+/// `redis_module::FromRedisArg` implementation which parses one of the
+/// variants from its token, which defaults to the upper case name of the
+/// variant.
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[derive(RedisArgs)]
+/// enum Condition {
+///     Nx,
+///     Xx,
+/// }
+///
+/// #[derive(RedisArgs)]
+/// struct Limit {
+///     offset: i64,
+///     count: i64,
+/// }
+///
+/// // key [NX | XX] member [member ...] [LIMIT offset count] [WITHSCORES]
+/// #[derive(RedisArgs)]
+/// struct AddArgs {
+///     #[RedisArgsAttr{key_spec_index: 0}]
+///     key: RedisString,
+///     condition: Option<Condition>,
+///     members: Vec<String>,
+///     #[RedisArgsAttr{token: "LIMIT", block: true}]
+///     limit: Option<Limit>,
+///     #[RedisArgsAttr{token: "WITHSCORES"}]
+///     with_scores: bool,
+/// }
+///
+/// fn add(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+///     let args = AddArgs::from_args(args.into_iter().skip(1))?;
+///     ...
+/// }
+/// ```
+///
+/// The `args_type` argument of the [`macro@command`] macro sets the
+/// arguments of the command in `COMMAND DOCS` from the struct.
+#[proc_macro_derive(RedisArgs, attributes(RedisArgsAttr))]
+pub fn redis_args(item: TokenStream) -> TokenStream {
+    redis_args::redis_args(item)
+}
+
 /// A procedural macro which registers this function as the custom
 /// `INFO` command handler. There might be more than one handler, each
 /// adding new information to the context.
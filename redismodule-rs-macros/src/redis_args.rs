@@ -0,0 +1,461 @@
+use proc_macro::TokenStream;
+use proc_macro2::Ident;
+use quote::quote;
+use serde::Deserialize;
+use serde_syn::{config, from_stream};
+use syn::{
+    parse,
+    parse::{Parse, ParseStream},
+    parse_macro_input, Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, GenericArgument,
+    PathArguments, Type,
+};
+
+/// Represent the attributes of a single field or variant.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct ArgAttr {
+    /// The name of the argument in `COMMAND DOCS`, defaults to the field name.
+    name: Option<String>,
+    /// The token preceding the argument, e.g. `COUNT`.
+    token: Option<String>,
+    /// A short description of the argument.
+    summary: Option<String>,
+    /// The index of the key spec covering the argument, which is a key.
+    key_spec_index: Option<i32>,
+    /// The field is a group of arguments implementing `RedisArgs`.
+    block: bool,
+}
+
+impl Parse for ArgAttr {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        from_stream(config::JSONY, input)
+    }
+}
+
+fn arg_attr(attrs: Vec<Attribute>) -> Result<ArgAttr, String> {
+    let mut attrs = attrs
+        .into_iter()
+        .filter(|attr| attr.path.is_ident("RedisArgsAttr"));
+    let attr = match attrs.next() {
+        Some(attr) => attr,
+        None => return Ok(ArgAttr::default()),
+    };
+    if attrs.next().is_some() {
+        return Err("Expected at most a single RedisArgsAttr attribute for each field".to_owned());
+    }
+    parse_macro_input::parse(attr.tokens.into()).map_err(|e| format!("{e}"))
+}
+
+/// Returns the type wrapped by `wrapper`, e.g. `T` of `Option<T>`.
+fn wrapped_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let segment = match ty {
+        Type::Path(p) => p.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != wrapper {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(t) => Some(t),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Path(p) if p.path.is_ident("bool"))
+}
+
+/// How many times an argument may appear.
+enum Occurrence {
+    Once,
+    Optional,
+    Multiple,
+    /// A token without a value.
+    Flag,
+}
+
+struct Field {
+    ident: Ident,
+    attr: ArgAttr,
+    occurrence: Occurrence,
+    /// The type of a single value of the field.
+    value_type: Type,
+}
+
+impl Field {
+    fn new(ident: Ident, ty: Type, attr: ArgAttr) -> Result<Field, String> {
+        let (occurrence, value_type) = if let Some(t) = wrapped_type(&ty, "Option") {
+            (Occurrence::Optional, t.clone())
+        } else if let Some(t) = wrapped_type(&ty, "Vec") {
+            (Occurrence::Multiple, t.clone())
+        } else if is_bool(&ty) {
+            if attr.token.is_none() {
+                return Err(format!("The bool field {ident} must have a token."));
+            }
+            (Occurrence::Flag, ty)
+        } else {
+            (Occurrence::Once, ty)
+        };
+        Ok(Field {
+            ident,
+            attr,
+            occurrence,
+            value_type,
+        })
+    }
+
+    /// The code parsing a single value of the field, following its token.
+    fn token_value(&self) -> proc_macro2::TokenStream {
+        let value_type = &self.value_type;
+        if self.attr.block {
+            quote! {redis_module::args::token_group::<#value_type, _>(args)?}
+        } else {
+            quote! {redis_module::args::token_value::<#value_type, _>(args)?}
+        }
+    }
+
+    /// The code describing the field for `COMMAND DOCS`.
+    fn command_arg(&self) -> proc_macro2::TokenStream {
+        let value_type = &self.value_type;
+        let name = self
+            .attr
+            .name
+            .clone()
+            .unwrap_or_else(|| self.ident.to_string());
+        let (arg_type, subargs) = match (&self.occurrence, self.attr.block) {
+            (Occurrence::Flag, _) => (
+                quote! {redis_module::commands::CommandArgType::PureToken},
+                quote! {Vec::new()},
+            ),
+            (_, true) => (
+                quote! {redis_module::commands::CommandArgType::Block},
+                quote! {<#value_type as redis_module::RedisArgs>::command_args()},
+            ),
+            _ if self.attr.key_spec_index.is_some() => (
+                quote! {redis_module::commands::CommandArgType::Key},
+                quote! {Vec::new()},
+            ),
+            _ => (
+                quote! {<#value_type as redis_module::FromRedisArg>::arg_type()},
+                quote! {<#value_type as redis_module::FromRedisArg>::subargs()},
+            ),
+        };
+        let flags = match (&self.occurrence, self.attr.token.is_some()) {
+            (Occurrence::Once, _) => quote! {redis_module::commands::CommandArgFlags::empty()},
+            (Occurrence::Optional | Occurrence::Flag, _) => {
+                quote! {redis_module::commands::CommandArgFlags::OPTIONAL}
+            }
+            (Occurrence::Multiple, false) => {
+                quote! {redis_module::commands::CommandArgFlags::MULTIPLE}
+            }
+            (Occurrence::Multiple, true) => quote! {
+                redis_module::commands::CommandArgFlags::OPTIONAL
+                    | redis_module::commands::CommandArgFlags::MULTIPLE_TOKEN
+            },
+        };
+        let key_spec_index = match self.attr.key_spec_index {
+            Some(v) => quote! {Some(#v)},
+            None => quote! {None},
+        };
+        let token = match &self.attr.token {
+            Some(v) => quote! {Some(#v)},
+            None => quote! {None},
+        };
+        let summary = match &self.attr.summary {
+            Some(v) => quote! {Some(#v)},
+            None => quote! {None},
+        };
+        quote! {
+            redis_module::args::command_arg(
+                #name,
+                #arg_type,
+                #key_spec_index,
+                #token,
+                #summary,
+                #flags,
+                #subargs,
+            )
+        }
+    }
+}
+
+/// Generate the code parsing a field without a token, `tokens` are the
+/// tokens of the fields following it, which end an optional or a
+/// repeated argument.
+fn positional_field(field: &Field, tokens: &[String]) -> proc_macro2::TokenStream {
+    let ident = &field.ident;
+    let value_type = &field.value_type;
+    let parse = match (&field.occurrence, field.attr.block) {
+        (Occurrence::Once, false) => {
+            quote! {redis_module::args::next_value::<#value_type, _>(args)?}
+        }
+        (Occurrence::Once, true) => {
+            quote! {<#value_type as redis_module::RedisArgs>::parse_args(args)?}
+        }
+        (Occurrence::Optional, false) => quote! {
+            redis_module::args::next_optional_value::<#value_type, _>(args, &[#(#tokens),*])?
+        },
+        (Occurrence::Optional, true) => quote! {
+            redis_module::args::next_optional_group::<#value_type, _>(args, &[#(#tokens),*])?
+        },
+        (Occurrence::Multiple, false) => quote! {
+            redis_module::args::next_values::<#value_type, _>(args, &[#(#tokens),*])?
+        },
+        (Occurrence::Multiple, true) => quote! {
+            redis_module::args::next_groups::<#value_type, _>(args, &[#(#tokens),*])?
+        },
+        (Occurrence::Flag, _) => unreachable!("flags always have a token"),
+    };
+    quote! {
+        let #ident = #parse;
+    }
+}
+
+/// Generate the code parsing consecutive fields with tokens, which may
+/// appear in any order.
+fn token_fields(fields: &[&Field]) -> proc_macro2::TokenStream {
+    let tokens: Vec<_> = fields
+        .iter()
+        .map(|f| f.attr.token.clone().unwrap().to_uppercase())
+        .collect();
+    let init: Vec<_> = fields
+        .iter()
+        .map(|f| {
+            let ident = &f.ident;
+            match f.occurrence {
+                Occurrence::Flag => quote! {let mut #ident = false;},
+                Occurrence::Multiple => quote! {let mut #ident = Vec::new();},
+                Occurrence::Once | Occurrence::Optional => quote! {let mut #ident = None;},
+            }
+        })
+        .collect();
+    let arms: Vec<_> = fields
+        .iter()
+        .map(|f| {
+            let ident = &f.ident;
+            let value = f.token_value();
+            match f.occurrence {
+                Occurrence::Flag => quote! {
+                    if #ident {
+                        return Err(redis_module::args::SYNTAX_ERROR);
+                    }
+                    #ident = true;
+                },
+                Occurrence::Multiple => quote! {
+                    #ident.push(#value);
+                },
+                Occurrence::Once | Occurrence::Optional => quote! {
+                    if #ident.is_some() {
+                        return Err(redis_module::args::SYNTAX_ERROR);
+                    }
+                    #ident = Some(#value);
+                },
+            }
+        })
+        .collect();
+    let required: Vec<_> = fields
+        .iter()
+        .filter(|f| matches!(f.occurrence, Occurrence::Once))
+        .map(|f| &f.ident)
+        .collect();
+    quote! {
+        #(#init)*
+        while let Some(token) = redis_module::args::peek_token(args, &[#(#tokens),*]) {
+            args.next();
+            match token {
+                #(#tokens => { #arms })*
+                _ => unreachable!(),
+            }
+        }
+        #(
+            let #required = #required.ok_or(redis_module::RedisError::WrongArity)?;
+        )*
+    }
+}
+
+/// Generate [RedisArgs] implementation for a struct. The fields are
+/// parsed in order, consecutive fields with a token are parsed in any
+/// order.
+fn struct_redis_args(struct_name: Ident, struct_data: DataStruct) -> TokenStream {
+    let fields = match struct_data.fields {
+        Fields::Named(f) => f,
+        _ => {
+            return quote! {compile_error!("RedisArgs derive can only be apply on struct with named fields.")}.into()
+        }
+    };
+
+    let fields = fields
+        .named
+        .into_iter()
+        .map(|v| {
+            let ident = v
+                .ident
+                .ok_or("Field without a name is not supported.".to_owned())?;
+            Field::new(ident, v.ty, arg_attr(v.attrs)?)
+        })
+        .collect::<Result<Vec<_>, String>>();
+
+    let fields = match fields {
+        Ok(f) => f,
+        Err(e) => return quote! {compile_error!(#e)}.into(),
+    };
+
+    let mut parse = Vec::new();
+    let mut group: Vec<&Field> = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        if field.attr.token.is_some() {
+            group.push(field);
+            continue;
+        }
+        if !group.is_empty() {
+            parse.push(token_fields(&group));
+            group.clear();
+        }
+        let tokens: Vec<_> = fields[i + 1..]
+            .iter()
+            .filter_map(|f| f.attr.token.as_ref().map(|t| t.to_uppercase()))
+            .collect();
+        parse.push(positional_field(field, &tokens));
+    }
+    if !group.is_empty() {
+        parse.push(token_fields(&group));
+    }
+
+    // An optional or repeated field without a token takes all the arguments
+    // it matches, leaving none to the fields without a token following it,
+    // unless its values are tokens.
+    let mut checks = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let greedy = field.attr.token.is_none()
+            && matches!(
+                field.occurrence,
+                Occurrence::Optional | Occurrence::Multiple
+            );
+        if !greedy || fields[i + 1..].iter().all(|f| f.attr.token.is_some()) {
+            continue;
+        }
+        let msg = format!(
+            "The field {} without a token may take the arguments of the fields without a token following it, \
+             give it a token or move it after them.",
+            field.ident
+        );
+        if field.attr.block {
+            return quote! {compile_error!(#msg);}.into();
+        }
+        let value_type = &field.value_type;
+        checks.push(quote! {
+            const _: () = assert!(<#value_type as redis_module::FromRedisArg>::IS_TOKEN, #msg);
+        });
+    }
+
+    let idents: Vec<_> = fields.iter().map(|f| &f.ident).collect();
+    let command_args: Vec<_> = fields.iter().map(Field::command_arg).collect();
+
+    let res = quote! {
+        #(#checks)*
+
+        impl redis_module::RedisArgs for #struct_name {
+            fn parse_args<I: Iterator<Item = redis_module::RedisString>>(
+                args: &mut std::iter::Peekable<I>,
+            ) -> Result<Self, redis_module::RedisError> {
+                #(#parse)*
+                Ok(#struct_name { #(#idents, )* })
+            }
+
+            fn command_args() -> Vec<redis_module::commands::CommandArg> {
+                vec![#(#command_args, )*]
+            }
+        }
+    };
+    res.into()
+}
+
+/// Generate [FromRedisArg] implementation for an enum of unit variants,
+/// each variant is parsed from its token, which defaults to the upper
+/// case name of the variant.
+fn enum_redis_args(enum_name: Ident, enum_data: DataEnum) -> TokenStream {
+    let variants = enum_data
+        .variants
+        .into_iter()
+        .map(|v| {
+            if !matches!(v.fields, Fields::Unit) {
+                return Err(
+                    "RedisArgs derive can only be apply on enum with unit variants.".to_owned(),
+                );
+            }
+            let attr = arg_attr(v.attrs)?;
+            let token = attr
+                .token
+                .unwrap_or_else(|| v.ident.to_string())
+                .to_uppercase();
+            let name = attr.name.unwrap_or_else(|| token.to_lowercase());
+            Ok((v.ident, token, name))
+        })
+        .collect::<Result<Vec<_>, String>>();
+
+    let variants = match variants {
+        Ok(v) => v,
+        Err(e) => return quote! {compile_error!(#e)}.into(),
+    };
+
+    let idents: Vec<_> = variants.iter().map(|(ident, _, _)| ident).collect();
+    let tokens: Vec<_> = variants.iter().map(|(_, token, _)| token).collect();
+    let names: Vec<_> = variants.iter().map(|(_, _, name)| name).collect();
+
+    let res = quote! {
+        impl redis_module::FromRedisArg for #enum_name {
+            const IS_TOKEN: bool = true;
+
+            fn from_redis_arg(arg: redis_module::RedisString) -> Result<Self, redis_module::RedisError> {
+                match redis_module::args::find_token(&arg, &[#(#tokens),*]) {
+                    #(Some(#tokens) => Ok(#enum_name::#idents),)*
+                    _ => Err(redis_module::args::SYNTAX_ERROR),
+                }
+            }
+
+            fn matches(arg: &redis_module::RedisString) -> bool {
+                redis_module::args::is_token(arg, &[#(#tokens),*])
+            }
+
+            fn arg_type() -> redis_module::commands::CommandArgType {
+                redis_module::commands::CommandArgType::OneOf
+            }
+
+            fn subargs() -> Vec<redis_module::commands::CommandArg> {
+                vec![
+                    #(
+                        redis_module::args::command_arg(
+                            #names,
+                            redis_module::commands::CommandArgType::PureToken,
+                            None,
+                            Some(#tokens),
+                            None,
+                            redis_module::commands::CommandArgFlags::empty(),
+                            Vec::new(),
+                        ),
+                    )*
+                ]
+            }
+        }
+    };
+    res.into()
+}
+
+/// Implementation for [RedisArgs] derive proc macro.
+/// Runs the relevant code generation base on the element
+/// the proc macro was used on. Currently supports Enums and
+/// structs.
+pub fn redis_args(item: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(item);
+    let name = input.ident;
+    match input.data {
+        Data::Struct(s) => struct_redis_args(name, s),
+        Data::Enum(e) => enum_redis_args(name, e),
+        _ => {
+            quote! {compile_error!("RedisArgs derive can only be apply on struct or enum.")}.into()
+        }
+    }
+}
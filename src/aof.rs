@@ -0,0 +1,147 @@
+use std::ffi::CString;
+use std::ptr;
+
+use crate::context::StrCallArgs;
+use crate::{raw, RedisString};
+
+/// An argument of a command emitted with [`AofEmitter::emit_args`].
+///
+/// Numbers are emitted in their decimal representation, which Redis parses
+/// back when the AOF is loaded.
+#[derive(Debug, Clone, Copy)]
+pub enum AofArg<'a> {
+    Buffer(&'a [u8]),
+    String(&'a RedisString),
+    Integer(i64),
+    Unsigned(u64),
+    Double(f64),
+}
+
+impl<'a> From<&'a [u8]> for AofArg<'a> {
+    fn from(value: &'a [u8]) -> Self {
+        Self::Buffer(value)
+    }
+}
+
+impl<'a> From<&'a Vec<u8>> for AofArg<'a> {
+    fn from(value: &'a Vec<u8>) -> Self {
+        Self::Buffer(value)
+    }
+}
+
+impl<'a> From<&'a str> for AofArg<'a> {
+    fn from(value: &'a str) -> Self {
+        Self::Buffer(value.as_bytes())
+    }
+}
+
+impl<'a> From<&'a String> for AofArg<'a> {
+    fn from(value: &'a String) -> Self {
+        Self::Buffer(value.as_bytes())
+    }
+}
+
+impl<'a> From<&'a RedisString> for AofArg<'a> {
+    fn from(value: &'a RedisString) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<i64> for AofArg<'_> {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<u64> for AofArg<'_> {
+    fn from(value: u64) -> Self {
+        Self::Unsigned(value)
+    }
+}
+
+impl From<usize> for AofArg<'_> {
+    fn from(value: usize) -> Self {
+        Self::Unsigned(value as u64)
+    }
+}
+
+impl From<f64> for AofArg<'_> {
+    fn from(value: f64) -> Self {
+        Self::Double(value)
+    }
+}
+
+fn number_string<T: ToString>(value: T) -> RedisString {
+    RedisString::create_from_slice(ptr::null_mut(), value.to_string().as_bytes())
+}
+
+/// Emits the commands which recreate a value of a custom data type when
+/// the AOF is rewritten, see
+/// [`crate::native_types::RedisDataType::aof_rewrite`].
+pub struct AofEmitter {
+    aof: *mut raw::RedisModuleIO,
+}
+
+impl AofEmitter {
+    #[must_use]
+    pub const fn new(aof: *mut raw::RedisModuleIO) -> Self {
+        Self { aof }
+    }
+
+    /// Emits `command` with `args` to the AOF.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_EmitAOF` is missing in redismodule.h
+    pub fn emit<'a, T: Into<StrCallArgs<'a>>>(&mut self, command: &str, args: T) {
+        let mut call_args: StrCallArgs = args.into();
+        self.emit_raw(command, call_args.args_mut());
+    }
+
+    /// Emits `command` with arguments of mixed types to the AOF, for
+    /// example a key name followed by a score:
+    ///
+    /// ```rust,no_run,ignore
+    /// aof.emit_args("MYTYPE.ADD", &[key.into(), score.into(), member.into()]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_EmitAOF` is missing in redismodule.h
+    pub fn emit_args(&mut self, command: &str, args: &[AofArg]) {
+        // The strings created for the arguments which are not already
+        // Redis strings, freed once the command is emitted.
+        let mut created = Vec::new();
+        let final_args: Vec<_> = args
+            .iter()
+            .map(|arg| {
+                let string = match arg {
+                    AofArg::String(string) => return string.inner,
+                    AofArg::Buffer(buffer) => {
+                        RedisString::create_from_slice(ptr::null_mut(), buffer)
+                    }
+                    AofArg::Integer(value) => number_string(value),
+                    AofArg::Unsigned(value) => number_string(value),
+                    AofArg::Double(value) => number_string(value),
+                };
+                let inner = string.inner;
+                created.push(string);
+                inner
+            })
+            .collect();
+        self.emit_raw(command, &final_args);
+    }
+
+    fn emit_raw(&mut self, command: &str, args: &[*mut raw::RedisModuleString]) {
+        let command = CString::new(command).unwrap();
+        unsafe {
+            raw::RedisModule_EmitAOF.unwrap()(
+                self.aof,
+                command.as_ptr(),
+                raw::FMT,
+                args.as_ptr(),
+                args.len(),
+            )
+        };
+    }
+}
@@ -0,0 +1,261 @@
+//! Parsing of command arguments into typed values, the runtime side of the
+//! `RedisArgs` derive macro.
+//!
+//! [`FromRedisArg`] is implemented by the types of a single argument, and
+//! [`RedisArgs`] by structs parsed from a sequence of arguments. Both also
+//! describe themselves as [`CommandArg`]s, so the `COMMAND DOCS` of a
+//! command are generated from the same definition which parses it.
+
+use std::iter::Peekable;
+
+use crate::commands::{CommandArg, CommandArgFlags, CommandArgType};
+use crate::{RedisError, RedisString};
+
+/// The error returned for unexpected arguments, duplicated tokens and
+/// tokens without a value.
+pub const SYNTAX_ERROR: RedisError = RedisError::Str("ERR syntax error");
+
+/// A type which is parsed from a single command argument.
+pub trait FromRedisArg: Sized {
+    /// Whether the argument is one of a set of tokens, as for the enums
+    /// deriving `RedisArgs`. Only such arguments may be optional or repeated
+    /// without a token of their own before other arguments without a token,
+    /// which they would otherwise take.
+    const IS_TOKEN: bool = false;
+
+    fn from_redis_arg(arg: RedisString) -> Result<Self, RedisError>;
+
+    /// Returns whether the argument may be parsed as this type. Optional
+    /// and repeated arguments stop at the first argument which does not
+    /// match.
+    fn matches(_arg: &RedisString) -> bool {
+        true
+    }
+
+    /// The type of the argument, shown by `COMMAND DOCS`.
+    fn arg_type() -> CommandArgType {
+        CommandArgType::String
+    }
+
+    /// The sub arguments of the argument, for [`CommandArgType::OneOf`].
+    fn subargs() -> Vec<CommandArg> {
+        Vec::new()
+    }
+}
+
+impl FromRedisArg for RedisString {
+    fn from_redis_arg(arg: RedisString) -> Result<Self, RedisError> {
+        Ok(arg)
+    }
+}
+
+impl FromRedisArg for String {
+    fn from_redis_arg(arg: RedisString) -> Result<Self, RedisError> {
+        Ok(arg.to_string_lossy())
+    }
+}
+
+impl FromRedisArg for i64 {
+    fn from_redis_arg(arg: RedisString) -> Result<Self, RedisError> {
+        arg.parse_integer()
+    }
+
+    fn arg_type() -> CommandArgType {
+        CommandArgType::Integer
+    }
+}
+
+impl FromRedisArg for u64 {
+    fn from_redis_arg(arg: RedisString) -> Result<Self, RedisError> {
+        arg.parse_unsigned_integer()
+    }
+
+    fn arg_type() -> CommandArgType {
+        CommandArgType::Integer
+    }
+}
+
+impl FromRedisArg for usize {
+    fn from_redis_arg(arg: RedisString) -> Result<Self, RedisError> {
+        usize::try_from(arg.parse_unsigned_integer()?)
+            .map_err(|_| RedisError::Str("Couldn't parse as integer"))
+    }
+
+    fn arg_type() -> CommandArgType {
+        CommandArgType::Integer
+    }
+}
+
+impl FromRedisArg for f64 {
+    fn from_redis_arg(arg: RedisString) -> Result<Self, RedisError> {
+        arg.parse_float()
+    }
+
+    fn arg_type() -> CommandArgType {
+        CommandArgType::Double
+    }
+}
+
+/// A type which is parsed from a sequence of command arguments, usually
+/// implemented with `#[derive(RedisArgs)]`.
+pub trait RedisArgs: Sized {
+    /// Parses the value from the next arguments, leaving the arguments
+    /// which follow it.
+    fn parse_args<I: Iterator<Item = RedisString>>(
+        args: &mut Peekable<I>,
+    ) -> Result<Self, RedisError>;
+
+    /// The arguments of the value, shown by `COMMAND DOCS`.
+    fn command_args() -> Vec<CommandArg>;
+
+    /// Parses the value from all the given arguments, e.g.
+    /// `args.into_iter().skip(1)` to skip the command name, and returns
+    /// [`SYNTAX_ERROR`] if any argument is left.
+    fn from_args<I: IntoIterator<Item = RedisString>>(args: I) -> Result<Self, RedisError> {
+        let mut args = args.into_iter().peekable();
+        let res = Self::parse_args(&mut args)?;
+        match args.peek() {
+            Some(_) => Err(SYNTAX_ERROR),
+            None => Ok(res),
+        }
+    }
+}
+
+/// Returns whether the argument is one of the tokens, which are compared
+/// case insensitively.
+#[must_use]
+pub fn is_token(arg: &RedisString, tokens: &[&str]) -> bool {
+    let arg = arg.as_slice();
+    tokens
+        .iter()
+        .any(|token| arg.eq_ignore_ascii_case(token.as_bytes()))
+}
+
+/// Returns the token matching the argument, if any.
+#[must_use]
+pub fn find_token(arg: &RedisString, tokens: &[&'static str]) -> Option<&'static str> {
+    let arg = arg.as_slice();
+    tokens
+        .iter()
+        .find(|token| arg.eq_ignore_ascii_case(token.as_bytes()))
+        .copied()
+}
+
+/// Returns the token matching the next argument, if any.
+pub fn peek_token<I: Iterator<Item = RedisString>>(
+    args: &mut Peekable<I>,
+    tokens: &[&'static str],
+) -> Option<&'static str> {
+    args.peek().and_then(|arg| find_token(arg, tokens))
+}
+
+/// Parses the next argument, returns [`RedisError::WrongArity`] if there
+/// is none.
+pub fn next_value<T: FromRedisArg, I: Iterator<Item = RedisString>>(
+    args: &mut Peekable<I>,
+) -> Result<T, RedisError> {
+    T::from_redis_arg(args.next().ok_or(RedisError::WrongArity)?)
+}
+
+/// Parses the next argument if it matches the type and is none of the
+/// tokens of the arguments which follow it.
+pub fn next_optional_value<T: FromRedisArg, I: Iterator<Item = RedisString>>(
+    args: &mut Peekable<I>,
+    tokens: &[&str],
+) -> Result<Option<T>, RedisError> {
+    match args.peek() {
+        Some(arg) if T::matches(arg) && !is_token(arg, tokens) => next_value(args).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Parses a group of arguments if the next argument is none of the tokens
+/// of the arguments which follow it.
+pub fn next_optional_group<T: RedisArgs, I: Iterator<Item = RedisString>>(
+    args: &mut Peekable<I>,
+    tokens: &[&str],
+) -> Result<Option<T>, RedisError> {
+    match args.peek() {
+        Some(arg) if !is_token(arg, tokens) => T::parse_args(args).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Parses the next arguments as long as they match the type and are none
+/// of the tokens of the arguments which follow them, returns
+/// [`RedisError::WrongArity`] if there is none.
+pub fn next_values<T: FromRedisArg, I: Iterator<Item = RedisString>>(
+    args: &mut Peekable<I>,
+    tokens: &[&str],
+) -> Result<Vec<T>, RedisError> {
+    let mut values = Vec::new();
+    while let Some(value) = next_optional_value(args, tokens)? {
+        values.push(value);
+    }
+    if values.is_empty() {
+        return Err(RedisError::WrongArity);
+    }
+    Ok(values)
+}
+
+/// Parses groups of arguments as long as the next argument is none of the
+/// tokens of the arguments which follow them, returns
+/// [`RedisError::WrongArity`] if there is none.
+pub fn next_groups<T: RedisArgs, I: Iterator<Item = RedisString>>(
+    args: &mut Peekable<I>,
+    tokens: &[&str],
+) -> Result<Vec<T>, RedisError> {
+    let mut groups = Vec::new();
+    while matches!(args.peek(), Some(arg) if !is_token(arg, tokens)) {
+        groups.push(T::parse_args(args)?);
+    }
+    if groups.is_empty() {
+        return Err(RedisError::WrongArity);
+    }
+    Ok(groups)
+}
+
+/// Parses the value following a token which was already consumed,
+/// returns [`SYNTAX_ERROR`] if there is none.
+pub fn token_value<T: FromRedisArg, I: Iterator<Item = RedisString>>(
+    args: &mut Peekable<I>,
+) -> Result<T, RedisError> {
+    T::from_redis_arg(args.next().ok_or(SYNTAX_ERROR)?)
+}
+
+/// Parses the group of arguments following a token which was already
+/// consumed, returns [`SYNTAX_ERROR`] if there is none.
+pub fn token_group<T: RedisArgs, I: Iterator<Item = RedisString>>(
+    args: &mut Peekable<I>,
+) -> Result<T, RedisError> {
+    if args.peek().is_none() {
+        return Err(SYNTAX_ERROR);
+    }
+    T::parse_args(args)
+}
+
+/// Describes an argument for `COMMAND DOCS`, used by the `RedisArgs`
+/// derive macro.
+#[must_use]
+pub fn command_arg(
+    name: &str,
+    arg_type: CommandArgType,
+    key_spec_index: Option<i32>,
+    token: Option<&str>,
+    summary: Option<&str>,
+    flags: CommandArgFlags,
+    subargs: Vec<CommandArg>,
+) -> CommandArg {
+    CommandArg::new(
+        name.to_owned(),
+        arg_type,
+        key_spec_index,
+        token.map(str::to_owned),
+        summary.map(str::to_owned),
+        None,
+        flags,
+        None,
+        subargs,
+        None,
+    )
+}
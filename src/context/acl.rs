@@ -0,0 +1,266 @@
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+use redis_module_macros_internals::api;
+
+use crate::{raw, Context, RedisError, RedisString};
+
+/// The signature of `RedisModule_AddACLCategory`, which is not declared in
+/// the vendored redismodule.h.
+type AddAclCategoryFunc = unsafe extern "C" fn(*mut raw::RedisModuleCtx, *const c_char) -> c_int;
+
+/// A user created by the module, which is not part of the ACL users of
+/// Redis: it is not listed by `ACL LIST` and cannot be used with `AUTH`,
+/// only by the module to authenticate clients with
+/// [`Context::authenticate_client_with_user`].
+///
+/// Dropping the user disconnects the clients authenticated with it.
+pub struct ModuleUser {
+    user: *mut raw::RedisModuleUser,
+}
+
+// Module users are only used while Redis is locked, but the module may keep
+// them in a global.
+unsafe impl Send for ModuleUser {}
+
+impl ModuleUser {
+    /// Creates a user with no permissions, which is disabled until its ACL
+    /// rules are set with [`ModuleUser::set_acl`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_CreateModuleUser` is missing in redismodule.h,
+    /// or if the name contains a nul byte
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        let name = CString::new(name).unwrap();
+        let user = unsafe { raw::RedisModule_CreateModuleUser.unwrap()(name.as_ptr()) };
+        Self { user }
+    }
+
+    /// Applies a single ACL rule to the user, such as `on`, `+@read` or
+    /// `~key:*`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_SetModuleUserACL` is missing in redismodule.h,
+    /// or if the rule contains a nul byte
+    pub fn set_acl(&self, rule: &str) -> Result<(), RedisError> {
+        let rule = CString::new(rule).unwrap();
+        let res: raw::Status =
+            unsafe { raw::RedisModule_SetModuleUserACL.unwrap()(self.user, rule.as_ptr()) }.into();
+        match res {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(RedisError::String(format!(
+                "Invalid ACL rule '{}'",
+                rule.to_string_lossy()
+            ))),
+        }
+    }
+
+    api!(
+        [RedisModule_SetModuleUserACLString],
+        /// Applies the space separated ACL rules to the user, all of them or
+        /// none of them if one is invalid, in which case the error describes
+        /// the invalid rule.
+        pub fn set_acl_string(&self, ctx: &Context, rules: &str) -> Result<(), RedisError> {
+            let rules = CString::new(rules).unwrap();
+            let mut error = ptr::null_mut();
+            let res: raw::Status = unsafe {
+                RedisModule_SetModuleUserACLString(ctx.ctx, self.user, rules.as_ptr(), &mut error)
+            }
+            .into();
+            match res {
+                raw::Status::Ok => Ok(()),
+                raw::Status::Err if error.is_null() => {
+                    Err(RedisError::Str("Failed setting the ACL rules"))
+                }
+                raw::Status::Err => Err(RedisError::String(
+                    RedisString::from_redis_module_string(ptr::null_mut(), error).to_string_lossy(),
+                )),
+            }
+        }
+    );
+
+    api!(
+        [RedisModule_GetModuleUserACLString],
+        /// Returns the ACL rules of the user, in the format of `ACL LIST`.
+        pub fn acl_string(&self) -> RedisString {
+            RedisString::from_redis_module_string(ptr::null_mut(), unsafe {
+                RedisModule_GetModuleUserACLString(self.user)
+            })
+        }
+    );
+}
+
+impl Drop for ModuleUser {
+    fn drop(&mut self) {
+        unsafe { raw::RedisModule_FreeModuleUser.unwrap()(self.user) };
+    }
+}
+
+/// The reason of an `ACL LOG` entry, see [`Context::acl_add_log_entry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AclLogReason {
+    /// The user is not allowed to run a command.
+    Cmd,
+    /// The user is not allowed to access a key.
+    Key,
+    /// The user is not allowed to access a pubsub channel.
+    Channel,
+    /// The user failed to authenticate.
+    Auth,
+}
+
+impl From<AclLogReason> for raw::RedisModuleACLLogEntryReason {
+    fn from(reason: AclLogReason) -> Self {
+        match reason {
+            AclLogReason::Cmd => raw::RedisModuleACLLogEntryReason_REDISMODULE_ACL_LOG_CMD,
+            AclLogReason::Key => raw::RedisModuleACLLogEntryReason_REDISMODULE_ACL_LOG_KEY,
+            AclLogReason::Channel => raw::RedisModuleACLLogEntryReason_REDISMODULE_ACL_LOG_CHANNEL,
+            AclLogReason::Auth => raw::RedisModuleACLLogEntryReason_REDISMODULE_ACL_LOG_AUTH,
+        }
+    }
+}
+
+type DeauthenticatedCallback = Box<dyn FnOnce(u64)>;
+
+extern "C" fn deauthenticated_callback(client_id: u64, privdata: *mut c_void) {
+    let callback = unsafe { Box::from_raw(privdata.cast::<DeauthenticatedCallback>()) };
+    callback(client_id);
+}
+
+impl Context {
+    /// Authenticates the current client as `user`, and returns the id of
+    /// the client.
+    ///
+    /// `on_deauthenticated` is called with the id of the client once it is
+    /// no longer authenticated as `user`: when it authenticates as another
+    /// user, disconnects, or when `user` is dropped. The user must not be
+    /// dropped before then, unless the client should be disconnected.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_AuthenticateClientWithUser` is missing in redismodule.h
+    pub fn authenticate_client_with_user<F: FnOnce(u64) + 'static>(
+        &self,
+        user: &ModuleUser,
+        on_deauthenticated: F,
+    ) -> Result<u64, RedisError> {
+        let callback: Box<DeauthenticatedCallback> = Box::new(Box::new(on_deauthenticated));
+        let privdata = Box::into_raw(callback).cast::<c_void>();
+        let mut client_id = 0;
+        let res: raw::Status = unsafe {
+            raw::RedisModule_AuthenticateClientWithUser.unwrap()(
+                self.ctx,
+                user.user,
+                Some(deauthenticated_callback),
+                privdata,
+                &mut client_id,
+            )
+        }
+        .into();
+        match res {
+            raw::Status::Ok => Ok(client_id),
+            raw::Status::Err => {
+                drop(unsafe { Box::from_raw(privdata.cast::<DeauthenticatedCallback>()) });
+                Err(RedisError::Str("User is disabled"))
+            }
+        }
+    }
+
+    api!(
+        [RedisModule_ACLAddLogEntryByUserName],
+        /// Adds an entry to `ACL LOG` for the user with the given name, as
+        /// Redis does when it denies a command. `object` is the command,
+        /// key or channel which was denied, and is ignored for
+        /// [`AclLogReason::Auth`].
+        pub fn acl_add_log_entry(
+            &self,
+            user_name: &RedisString,
+            object: &RedisString,
+            reason: AclLogReason,
+        ) {
+            unsafe {
+                RedisModule_ACLAddLogEntryByUserName(
+                    self.ctx,
+                    user_name.inner,
+                    object.inner,
+                    reason.into(),
+                )
+            };
+        }
+    );
+
+    /// Adds an entry to `ACL LOG` for a module user, see
+    /// [`Context::acl_add_log_entry`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ACLAddLogEntry` is missing in redismodule.h
+    pub fn acl_add_log_entry_for_user(
+        &self,
+        user: &ModuleUser,
+        object: &RedisString,
+        reason: AclLogReason,
+    ) {
+        unsafe {
+            raw::RedisModule_ACLAddLogEntry.unwrap()(
+                self.ctx,
+                user.user,
+                object.inner,
+                reason.into(),
+            )
+        };
+    }
+
+    /// Adds an ACL category for the commands of the module, which can then
+    /// be used in the ACL rules of the users, e.g. `+@mymodule`, and given
+    /// to the commands of the module. It must be called while the module is
+    /// loading, before the commands using it are registered.
+    ///
+    /// `RedisModule_AddACLCategory` was added in Redis 7.4, which is newer
+    /// than the vendored redismodule.h, so it is looked up with
+    /// `RedisModule_GetApi` when called, and an error is returned if Redis
+    /// does not have it.
+    pub fn add_acl_category(&self, name: &str) -> Result<(), RedisError> {
+        let mut add_acl_category: Option<AddAclCategoryFunc> = None;
+        let res: raw::Status = unsafe {
+            raw::RedisModule_GetApi.unwrap()(
+                c"RedisModule_AddACLCategory".as_ptr(),
+                ptr::addr_of_mut!(add_acl_category).cast::<c_void>(),
+            )
+        }
+        .into();
+        let (raw::Status::Ok, Some(add_acl_category)) = (res, add_acl_category) else {
+            return Err(RedisError::Str(
+                "RedisModule_AddACLCategory does not exists",
+            ));
+        };
+        let name_c =
+            CString::new(name).map_err(|_| RedisError::Str("ACL category contains a nul byte"))?;
+        let res: raw::Status = unsafe { add_acl_category(self.ctx, name_c.as_ptr()) }.into();
+        match res {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(RedisError::String(format!(
+                "Failed adding ACL category {name}"
+            ))),
+        }
+    }
+
+    /// Deauthenticates the client and closes its connection, asynchronously.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DeauthenticateAndCloseClient` is missing in redismodule.h
+    pub fn deauthenticate_and_close_client(&self, client_id: u64) -> Result<(), RedisError> {
+        let res: raw::Status =
+            unsafe { raw::RedisModule_DeauthenticateAndCloseClient.unwrap()(self.ctx, client_id) }
+                .into();
+        match res {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(RedisError::Str("Client does not exist")),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::{raw, Context, RedisError, RedisString, Status};
+
+/// The result of an authentication callback registered with
+/// [`Context::register_auth_callback`], or of a blocked authentication,
+/// see [`BlockedAuthClient::unblock`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthResult {
+    /// Authenticates the client as the ACL user with the given username.
+    Allow,
+    /// Fails the authentication with the given error message.
+    Deny(String),
+    /// Leaves the authentication to the next callbacks, and eventually to
+    /// the passwords of the ACL users.
+    Skip,
+    /// The client was blocked with [`Context::block_client_on_auth`], the
+    /// result is given when it is unblocked.
+    Blocked,
+}
+
+type AuthCallback = Arc<dyn Fn(&Context, RedisString, RedisString) -> AuthResult + Send + Sync>;
+
+/// The authentication callbacks of the module, in the order they were
+/// registered. Redis calls a single callback of the module, which calls
+/// these in turn until one does not skip, on a copy of the list so the lock
+/// is not held while they run.
+static AUTH_CALLBACKS: Mutex<Vec<AuthCallback>> = Mutex::new(Vec::new());
+
+/// Applies the result of an authentication callback, returns
+/// `REDISMODULE_AUTH_NOT_HANDLED` for [`AuthResult::Skip`].
+fn handle_auth_result(
+    ctx: *mut raw::RedisModuleCtx,
+    username: *mut raw::RedisModuleString,
+    result: AuthResult,
+    err: *mut *mut raw::RedisModuleString,
+) -> c_int {
+    let deny = |message: &str| {
+        unsafe { *err = RedisString::create(None, message).take() };
+        raw::REDISMODULE_AUTH_HANDLED as c_int
+    };
+    match result {
+        AuthResult::Allow => {
+            let username = RedisString::string_as_slice(username);
+            let res: raw::Status = unsafe {
+                raw::RedisModule_AuthenticateClientWithACLUser.unwrap()(
+                    ctx,
+                    username.as_ptr().cast::<c_char>(),
+                    username.len(),
+                    None,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                )
+            }
+            .into();
+            match res {
+                raw::Status::Ok => raw::REDISMODULE_AUTH_HANDLED as c_int,
+                raw::Status::Err => deny("User does not exist or is disabled"),
+            }
+        }
+        AuthResult::Deny(message) => deny(&message),
+        AuthResult::Skip => raw::REDISMODULE_AUTH_NOT_HANDLED as c_int,
+        AuthResult::Blocked => raw::REDISMODULE_AUTH_HANDLED as c_int,
+    }
+}
+
+extern "C" fn auth_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    username: *mut raw::RedisModuleString,
+    password: *mut raw::RedisModuleString,
+    err: *mut *mut raw::RedisModuleString,
+) -> c_int {
+    let context = Context::new(ctx);
+    let callbacks = AUTH_CALLBACKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    let result = callbacks
+        .iter()
+        .map(|callback| {
+            callback(
+                &context,
+                RedisString::new(None, username),
+                RedisString::new(None, password),
+            )
+        })
+        .find(|result| *result != AuthResult::Skip)
+        .unwrap_or(AuthResult::Skip);
+    handle_auth_result(ctx, username, result, err)
+}
+
+extern "C" fn blocked_auth_reply(
+    ctx: *mut raw::RedisModuleCtx,
+    username: *mut raw::RedisModuleString,
+    _password: *mut raw::RedisModuleString,
+    err: *mut *mut raw::RedisModuleString,
+) -> c_int {
+    let result = unsafe {
+        raw::RedisModule_GetBlockedClientPrivateData.unwrap()(ctx)
+            .cast::<AuthResult>()
+            .as_mut()
+    };
+    let result = match result {
+        Some(AuthResult::Blocked) | None => {
+            AuthResult::Deny("Authentication was not completed".to_string())
+        }
+        Some(result) => std::mem::replace(result, AuthResult::Skip),
+    };
+    handle_auth_result(ctx, username, result, err)
+}
+
+extern "C" fn blocked_auth_free(_ctx: *mut raw::RedisModuleCtx, privdata: *mut c_void) {
+    if !privdata.is_null() {
+        drop(unsafe { Box::from_raw(privdata.cast::<AuthResult>()) });
+    }
+}
+
+/// A client blocked during its authentication, which can be sent to
+/// another thread, e.g. to check the credentials against an external
+/// service, and unblocked from there.
+///
+/// Dropping the blocked client unblocks it, failing the authentication.
+pub struct BlockedAuthClient {
+    inner: *mut raw::RedisModuleBlockedClient,
+}
+
+// We need to be able to send the inner pointer to another thread
+unsafe impl Send for BlockedAuthClient {}
+
+impl BlockedAuthClient {
+    fn unblock_with(&mut self, result: Option<AuthResult>) -> Status {
+        let inner = std::mem::replace(&mut self.inner, ptr::null_mut());
+        let privdata = result.map_or(ptr::null_mut(), |result| {
+            Box::into_raw(Box::new(result)).cast::<c_void>()
+        });
+        unsafe { raw::RedisModule_UnblockClient.unwrap()(inner, privdata) }.into()
+    }
+
+    /// Unblocks the client, the result is applied on the main thread.
+    /// [`AuthResult::Skip`] leaves the authentication to the passwords of
+    /// the ACL users, the next callbacks of the module are not called.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_UnblockClient` is missing in redismodule.h
+    pub fn unblock(mut self, result: AuthResult) -> Status {
+        self.unblock_with(Some(result))
+    }
+}
+
+impl Drop for BlockedAuthClient {
+    fn drop(&mut self) {
+        if !self.inner.is_null() {
+            self.unblock_with(None);
+        }
+    }
+}
+
+impl Context {
+    /// Registers a callback called on `AUTH` and `HELLO AUTH`, with the
+    /// username and password, before they are checked against the ACL
+    /// users. The callbacks of the module are called in the order they were
+    /// registered, until one of them does not return [`AuthResult::Skip`].
+    pub fn register_auth_callback<F>(&self, callback: F) -> Result<(), RedisError>
+    where
+        F: Fn(&Context, RedisString, RedisString) -> AuthResult + Send + Sync + 'static,
+    {
+        let Some(register_auth_callback) = (unsafe { raw::RedisModule_RegisterAuthCallback })
+        else {
+            return Err(RedisError::Str(
+                "RedisModule_RegisterAuthCallback does not exists",
+            ));
+        };
+        let mut callbacks = AUTH_CALLBACKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if callbacks.is_empty() {
+            unsafe { register_auth_callback(self.ctx, Some(auth_callback)) };
+        }
+        callbacks.push(Arc::new(callback));
+        Ok(())
+    }
+
+    /// Blocks the client from an authentication callback, which must then
+    /// return [`AuthResult::Blocked`]. The result of the authentication is
+    /// given when unblocking the client.
+    pub fn block_client_on_auth(&self) -> Result<BlockedAuthClient, RedisError> {
+        let Some(block_client_on_auth) = (unsafe { raw::RedisModule_BlockClientOnAuth }) else {
+            return Err(RedisError::Str(
+                "RedisModule_BlockClientOnAuth does not exists",
+            ));
+        };
+        let inner = unsafe {
+            block_client_on_auth(self.ctx, Some(blocked_auth_reply), Some(blocked_auth_free))
+        };
+        if inner.is_null() {
+            return Err(RedisError::Str("Failed blocking the client"));
+        }
+        Ok(BlockedAuthClient { inner })
+    }
+}
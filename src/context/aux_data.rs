@@ -0,0 +1,302 @@
+use std::any::Any;
+use std::ffi::CString;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex};
+
+use num_traits::FromPrimitive;
+
+use crate::context::thread_safe::RedisGILGuardScope;
+use crate::error::Error;
+use crate::native_types::{redis_log, AuxSaveTriggers};
+use crate::raw::{self, Aux};
+use crate::rdb::{RdbReader, RdbWriter};
+use crate::{Context, RedisError, RedisGILGuard, RedisLockIndicator};
+
+type SaveCallback<S> = Box<dyn Fn(&S, &mut RdbWriter, Aux) + Send + Sync>;
+type LoadCallback<S> = Box<dyn Fn(&mut S, &mut RdbReader, Aux) -> Result<(), Error> + Send + Sync>;
+
+struct AuxDataInner<S> {
+    state: RedisGILGuard<S>,
+    on_save: SaveCallback<S>,
+    on_load: LoadCallback<S>,
+}
+
+/// The aux callbacks of a registration, without the type of its state.
+trait AuxDataCallbacks: Send + Sync {
+    fn save(&self, rdb: &mut RdbWriter, when: Aux);
+    fn load(&self, rdb: &mut RdbReader, when: Aux) -> Result<(), Error>;
+}
+
+impl<S> AuxDataCallbacks for AuxDataInner<S> {
+    fn save(&self, rdb: &mut RdbWriter, when: Aux) {
+        // The state is only accessed with the GIL held. The RDB is saved
+        // either with the GIL held or in a fork child, which was forked
+        // with the GIL held, so no lock is taken here.
+        (self.on_save)(unsafe { &*self.state.get() }, rdb, when);
+    }
+
+    fn load(&self, rdb: &mut RdbReader, when: Aux) -> Result<(), Error> {
+        (self.on_load)(unsafe { &mut *self.state.get() }, rdb, when)
+    }
+}
+
+/// Module-wide state persisted in the RDB, outside of any key, registered
+/// with [`Context::aux_data`].
+///
+/// The state is saved with every RDB, including the ones sent to replicas
+/// on full sync, and loaded back when the RDB is loaded.
+pub struct AuxData<S> {
+    inner: Arc<AuxDataInner<S>>,
+}
+
+impl<S> Clone for AuxData<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+/// The maximal number of aux data a module may register.
+pub const MAX_AUX_DATA: usize = 16;
+
+/// The callbacks of the registered aux data. The aux callbacks do not take
+/// any private data, so each slot gets its own callbacks which call the
+/// ones stored in it. Aux data is never unregistered, so the slots are read
+/// without a lock.
+static AUX_DATA_SLOTS: [AtomicPtr<Arc<dyn AuxDataCallbacks>>; MAX_AUX_DATA] =
+    [const { AtomicPtr::new(ptr::null_mut()) }; MAX_AUX_DATA];
+
+/// The registered aux data by name, for [`AuxData::get`].
+static AUX_DATA: Mutex<Vec<(String, Box<dyn Any + Send>)>> = Mutex::new(Vec::new());
+
+impl<S: Send + 'static> AuxData<S> {
+    /// Returns the aux data registered under `name`, if its state is of
+    /// type `S`.
+    #[must_use]
+    pub fn get(name: &str) -> Option<Self> {
+        AUX_DATA
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, aux_data)| aux_data.downcast_ref::<Self>())
+            .cloned()
+    }
+
+    /// Returns the state. Like [`RedisGILGuard`], the state is protected by
+    /// the Redis GIL rather than by a lock of its own, so it can be saved in
+    /// the fork child of `BGSAVE`.
+    pub fn lock<'ctx, G: RedisLockIndicator>(
+        &self,
+        ctx: &'ctx G,
+    ) -> RedisGILGuardScope<'ctx, '_, S, G> {
+        self.inner.state.lock(ctx)
+    }
+}
+
+fn aux_data_slot(slot: usize) -> Option<&'static Arc<dyn AuxDataCallbacks>> {
+    unsafe { AUX_DATA_SLOTS[slot].load(Ordering::Acquire).as_ref() }
+}
+
+extern "C" fn aux_save<const SLOT: usize>(rdb: *mut raw::RedisModuleIO, when: c_int) {
+    if let (Some(aux_data), Some(when)) = (aux_data_slot(SLOT), Aux::from_i32(when)) {
+        aux_data.save(&mut RdbWriter::new(rdb), when);
+    }
+}
+
+extern "C" fn aux_load<const SLOT: usize>(
+    rdb: *mut raw::RedisModuleIO,
+    encver: c_int,
+    when: c_int,
+) -> c_int {
+    let (aux_data, when) = match (aux_data_slot(SLOT), Aux::from_i32(when)) {
+        (Some(aux_data), Some(when)) => (aux_data, when),
+        _ => return raw::Status::Err as c_int,
+    };
+    match aux_data.load(&mut RdbReader::new(rdb, encver), when) {
+        Ok(()) => raw::Status::Ok as c_int,
+        Err(e) => {
+            redis_log(ptr::null_mut(), &format!("Failed loading aux data: {e}"));
+            raw::Status::Err as c_int
+        }
+    }
+}
+
+type AuxSaveFunc = extern "C" fn(*mut raw::RedisModuleIO, c_int);
+type AuxLoadFunc = extern "C" fn(*mut raw::RedisModuleIO, c_int, c_int) -> c_int;
+
+macro_rules! aux_data_callbacks {
+    ($($slot:literal),*) => {
+        [$((aux_save::<$slot> as AuxSaveFunc, aux_load::<$slot> as AuxLoadFunc)),*]
+    };
+}
+
+static AUX_DATA_CALLBACKS: [(AuxSaveFunc, AuxLoadFunc); MAX_AUX_DATA] =
+    aux_data_callbacks!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+
+// The data type of the aux data has no values, it only exists to carry the
+// aux callbacks.
+
+extern "C" fn rdb_load(_rdb: *mut raw::RedisModuleIO, _encver: c_int) -> *mut c_void {
+    ptr::null_mut()
+}
+
+extern "C" fn rdb_save(_rdb: *mut raw::RedisModuleIO, _value: *mut c_void) {}
+
+extern "C" fn free(_value: *mut c_void) {}
+
+/// Registers aux data, see [`Context::aux_data`].
+pub struct AuxDataBuilder<'ctx, S> {
+    ctx: &'ctx Context,
+    name: &'ctx str,
+    encver: i32,
+    triggers: AuxSaveTriggers,
+    inner: AuxDataInner<S>,
+}
+
+impl<'ctx, S: Send + 'static> AuxDataBuilder<'ctx, S> {
+    /// The version of the encoding of the state, passed to the load
+    /// callback through [`RdbReader::encver`]. Defaults to 0.
+    pub fn encver(mut self, encver: i32) -> Self {
+        self.encver = encver;
+        self
+    }
+
+    /// When the state is saved, before or after the keys. Defaults to
+    /// [`AuxSaveTriggers::BEFORE_RDB`], so the state is loaded before the
+    /// keys.
+    pub fn triggers(mut self, triggers: AuxSaveTriggers) -> Self {
+        self.triggers = triggers;
+        self
+    }
+
+    /// Registers the aux data. It must be called while the module is
+    /// loaded, and each name can only be registered once.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_CreateDataType` is missing in redismodule.h
+    pub fn register(self) -> Result<AuxData<S>, RedisError> {
+        if self.name.len() != 9 {
+            return Err(RedisError::Str(
+                "Redis requires the length of native type names to be exactly 9 characters",
+            ));
+        }
+        let name = CString::new(self.name)
+            .map_err(|_| RedisError::Str("The name of aux data must not contain a nul byte"))?;
+        let mut registered = AUX_DATA.lock().unwrap();
+        if registered.iter().any(|(n, _)| n == self.name) {
+            return Err(RedisError::Str("Aux data is already registered"));
+        }
+
+        let inner = Arc::new(self.inner);
+        let callbacks: Arc<dyn AuxDataCallbacks> = inner.clone();
+        let callbacks = Box::into_raw(Box::new(callbacks));
+        let slot = AUX_DATA_SLOTS.iter().position(|slot| {
+            slot.compare_exchange(
+                ptr::null_mut(),
+                callbacks,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+        });
+        let Some(slot) = slot else {
+            drop(unsafe { Box::from_raw(callbacks) });
+            return Err(RedisError::Str("Too many aux data are registered"));
+        };
+        let (aux_save, aux_load) = AUX_DATA_CALLBACKS[slot];
+
+        let mut type_methods = raw::RedisModuleTypeMethods {
+            version: raw::REDISMODULE_TYPE_METHOD_VERSION as u64,
+            rdb_load: Some(rdb_load),
+            rdb_save: Some(rdb_save),
+            aof_rewrite: None,
+            free: Some(free),
+            mem_usage: None,
+            digest: None,
+            aux_load: Some(aux_load),
+            // Servers which know aux_save2 use it instead of aux_save, and
+            // save nothing when the callback writes nothing.
+            aux_save: Some(aux_save),
+            aux_save2: Some(aux_save),
+            aux_save_triggers: self.triggers.bits(),
+            free_effort: None,
+            unlink: None,
+            copy: None,
+            defrag: None,
+            copy2: None,
+            free_effort2: None,
+            mem_usage2: None,
+            unlink2: None,
+        };
+        let redis_type = unsafe {
+            raw::RedisModule_CreateDataType.unwrap()(
+                self.ctx.ctx,
+                name.as_ptr(),
+                self.encver,
+                &mut type_methods,
+            )
+        };
+        if redis_type.is_null() {
+            // The data type was not created, so the callbacks of the slot
+            // are never called.
+            let callbacks = AUX_DATA_SLOTS[slot].swap(ptr::null_mut(), Ordering::AcqRel);
+            drop(unsafe { Box::from_raw(callbacks) });
+            return Err(RedisError::String(format!(
+                "Failed creating the data type of aux data '{}'",
+                self.name
+            )));
+        }
+
+        let aux_data = AuxData { inner };
+        registered.push((self.name.to_owned(), Box::new(aux_data.clone())));
+        Ok(aux_data)
+    }
+}
+
+impl Context {
+    /// Persists the module-wide `state` in the RDB, outside of any key.
+    ///
+    /// `name` names the data type Redis stores the state under, it must be
+    /// exactly 9 characters long and unique among all the data types.
+    /// `on_save` writes the state to the RDB and `on_load` reads it back,
+    /// see [`crate::native_types::RedisDataType::aux_load`] for when they
+    /// are called. Since Redis 7.2, nothing is saved when `on_save` writes
+    /// nothing, so an RDB saved with such a state can be loaded without the
+    /// module. Older versions always call `on_load`.
+    ///
+    /// `on_save` may run in a fork child, it must not take locks which
+    /// other threads may hold. The state itself is protected by the GIL,
+    /// see [`AuxData::lock`].
+    ///
+    /// The registered state is returned by [`AuxDataBuilder::register`] and
+    /// [`AuxData::get`].
+    pub fn aux_data<'ctx, S, SaveF, LoadF>(
+        &'ctx self,
+        name: &'ctx str,
+        state: S,
+        on_save: SaveF,
+        on_load: LoadF,
+    ) -> AuxDataBuilder<'ctx, S>
+    where
+        S: Send + 'static,
+        SaveF: Fn(&S, &mut RdbWriter, Aux) + Send + Sync + 'static,
+        LoadF: Fn(&mut S, &mut RdbReader, Aux) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        AuxDataBuilder {
+            ctx: self,
+            name,
+            encver: 0,
+            triggers: AuxSaveTriggers::BEFORE_RDB,
+            inner: AuxDataInner {
+                state: RedisGILGuard::new(state),
+                on_save: Box::new(on_save),
+                on_load: Box::new(on_load),
+            },
+        }
+    }
+}
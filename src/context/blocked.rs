@@ -1,18 +1,436 @@
-use std::ptr;
+use std::collections::BTreeMap;
+use std::os::raw::{c_int, c_longlong, c_void};
+use std::ptr::{self, NonNull};
+use std::sync::Mutex;
+use std::time::Duration;
 
-use crate::raw;
-use crate::Context;
+use bitflags::bitflags;
 
-pub struct BlockedClient {
+use crate::{raw, Context, RedisError, RedisResult, RedisString, RedisValue, Status};
+
+type ReplyCallback<T> = Box<dyn FnOnce(&Context, T) -> RedisResult + Send>;
+type TimeoutCallback = Box<dyn FnOnce(&Context) -> RedisResult + Send>;
+type DisconnectCallback = Box<dyn FnOnce(&Context) + Send>;
+
+#[derive(Default)]
+struct BlockedClientCallbacks {
+    on_timeout: Option<TimeoutCallback>,
+    on_disconnect: Option<DisconnectCallback>,
+}
+
+/// The timeout and disconnect callbacks of the blocked clients, indexed by
+/// blocked client. Redis only hands private data to the reply and free
+/// callbacks, so the other callbacks are looked up here.
+static BLOCKED_CLIENT_CALLBACKS: Mutex<BTreeMap<usize, BlockedClientCallbacks>> =
+    Mutex::new(BTreeMap::new());
+
+/// The private data a blocked client is unblocked with, handed to the reply
+/// callback on the main thread.
+struct Unblocked<T> {
+    client: usize,
+    value: Option<T>,
+    reply: Option<ReplyCallback<T>>,
+}
+
+/// A client blocked by the current command, which can be sent to another
+/// thread and unblocked from there.
+///
+/// Clients blocked with [`Context::block_client_with_reply`] are unblocked
+/// with [`BlockedClient::unblock`], the value is then passed to the reply
+/// callback on the main thread. Dropping the blocked client unblocks it
+/// without calling the reply callback: the client gets an error, unless it
+/// was handed to [`crate::ThreadSafeContext::with_blocked_client`] to reply
+/// through it.
+pub struct BlockedClient<T = ()> {
     pub(crate) inner: *mut raw::RedisModuleBlockedClient,
+    pub(crate) reply: Option<ReplyCallback<T>>,
+    /// Clients blocked with callbacks are always unblocked with private data,
+    /// so that the free callback cleans up after them.
+    with_callbacks: bool,
 }
 
 // We need to be able to send the inner pointer to another thread
-unsafe impl Send for BlockedClient {}
+unsafe impl<T: Send> Send for BlockedClient<T> {}
+
+impl<T> BlockedClient<T> {
+    fn unblock_with(&mut self, value: Option<T>) -> Status {
+        let inner = std::mem::replace(&mut self.inner, ptr::null_mut());
+        let privdata = if self.with_callbacks {
+            let unblocked = Unblocked {
+                client: inner as usize,
+                value,
+                reply: self.reply.take(),
+            };
+            Box::into_raw(Box::new(unblocked)).cast::<c_void>()
+        } else {
+            ptr::null_mut()
+        };
+        unsafe { raw::RedisModule_UnblockClient.unwrap()(inner, privdata) }.into()
+    }
+
+    /// Unblocks the client, `value` is passed to the reply callback on the
+    /// main thread.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_UnblockClient` is missing in redismodule.h
+    pub fn unblock(mut self, value: T) -> Status {
+        self.unblock_with(Some(value))
+    }
 
-impl Drop for BlockedClient {
+    /// Unblocks the client without calling any of its callbacks. This is
+    /// meant to be called from the command that blocked the client, which is
+    /// then in charge of replying, e.g. when the background work could not
+    /// be started.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_AbortBlock` is missing in redismodule.h
+    pub fn abort(mut self) -> Status {
+        let inner = std::mem::replace(&mut self.inner, ptr::null_mut());
+        BLOCKED_CLIENT_CALLBACKS
+            .lock()
+            .unwrap()
+            .remove(&(inner as usize));
+        unsafe { raw::RedisModule_AbortBlock.unwrap()(inner) }.into()
+    }
+
+    /// Starts measuring the time spent on behalf of the blocked client, e.g.
+    /// by a background thread, so that it is accounted for in `SLOWLOG` and
+    /// `LATENCY`. Can be called several times, the measured intervals add up.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_BlockedClientMeasureTimeStart` is missing in redismodule.h
+    pub fn measure_time_start(&self) -> Status {
+        unsafe { raw::RedisModule_BlockedClientMeasureTimeStart.unwrap()(self.inner) }.into()
+    }
+
+    /// Stops measuring the time started with
+    /// [`BlockedClient::measure_time_start`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_BlockedClientMeasureTimeEnd` is missing in redismodule.h
+    pub fn measure_time_end(&self) -> Status {
+        unsafe { raw::RedisModule_BlockedClientMeasureTimeEnd.unwrap()(self.inner) }.into()
+    }
+}
+
+impl<T> Drop for BlockedClient<T> {
     fn drop(&mut self) {
-        unsafe { raw::RedisModule_UnblockClient.unwrap()(self.inner, ptr::null_mut()) };
+        if !self.inner.is_null() {
+            self.unblock_with(None);
+        }
+    }
+}
+
+/// Blocks the client of the current command until it is unblocked with a
+/// value, see [`Context::block_client_with_reply`].
+#[must_use]
+pub struct BlockClientBuilder<'ctx, T> {
+    ctx: &'ctx Context,
+    timeout: Duration,
+    reply: ReplyCallback<T>,
+    callbacks: BlockedClientCallbacks,
+}
+
+impl<'ctx, T: Send + 'static> BlockClientBuilder<'ctx, T> {
+    /// The maximum time to wait for the client to be unblocked, the client
+    /// is blocked without a timeout by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Called when the timeout is reached, the result is sent to the client.
+    /// Without it, the client gets a null reply on timeout.
+    ///
+    /// The blocked client must still be unblocked or dropped afterwards, the
+    /// reply callback is then not called.
+    pub fn on_timeout<F: FnOnce(&Context) -> RedisResult + Send + 'static>(
+        mut self,
+        on_timeout: F,
+    ) -> Self {
+        self.callbacks.on_timeout = Some(Box::new(on_timeout));
+        self
+    }
+
+    /// Called when the client disconnects while it is blocked, e.g. to stop
+    /// the background work early.
+    ///
+    /// The blocked client must still be unblocked or dropped afterwards, the
+    /// reply callback is then not called.
+    pub fn on_disconnect<F: FnOnce(&Context) + Send + 'static>(mut self, on_disconnect: F) -> Self {
+        self.callbacks.on_disconnect = Some(Box::new(on_disconnect));
+        self
+    }
+
+    /// Blocks the client. The command must then return
+    /// [`RedisValue::NoReply`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_BlockClient` is missing in redismodule.h
+    pub fn block(self) -> BlockedClient<T> {
+        let timeout = c_longlong::try_from(self.timeout.as_millis()).unwrap_or(c_longlong::MAX);
+        let inner = unsafe {
+            raw::RedisModule_BlockClient.unwrap()(
+                self.ctx.ctx,
+                Some(blocked_client_reply::<T>),
+                Some(blocked_client_timeout),
+                Some(blocked_client_free::<T>),
+                timeout,
+            )
+        };
+        if self.callbacks.on_disconnect.is_some() {
+            unsafe {
+                raw::RedisModule_SetDisconnectCallback.unwrap()(
+                    inner,
+                    Some(blocked_client_disconnect),
+                );
+            }
+        }
+        BLOCKED_CLIENT_CALLBACKS
+            .lock()
+            .unwrap()
+            .insert(inner as usize, self.callbacks);
+        BlockedClient {
+            inner,
+            reply: Some(self.reply),
+            with_callbacks: true,
+        }
+    }
+}
+
+extern "C" fn blocked_client_reply<T>(
+    ctx: *mut raw::RedisModuleCtx,
+    _argv: *mut *mut raw::RedisModuleString,
+    _argc: c_int,
+) -> c_int {
+    let unblocked = unsafe {
+        raw::RedisModule_GetBlockedClientPrivateData.unwrap()(ctx).cast::<Unblocked<T>>()
+    };
+    let Some(unblocked) = (unsafe { unblocked.as_mut() }) else {
+        return raw::Status::Ok as c_int;
+    };
+    match (unblocked.reply.take(), unblocked.value.take()) {
+        (Some(reply), Some(value)) => {
+            let context = Context::new(ctx);
+            context.reply(reply(&context, value)) as c_int
+        }
+        // The client was dropped without a value, e.g. the thread in charge
+        // of it panicked, it would otherwise never get a reply.
+        (Some(_), None) => Context::new(ctx).reply(Err(RedisError::Str(
+            "ERR blocked client was dropped without a reply",
+        ))) as c_int,
+        // The reply was sent through a thread safe context.
+        _ => raw::Status::Ok as c_int,
+    }
+}
+
+extern "C" fn blocked_client_timeout(
+    ctx: *mut raw::RedisModuleCtx,
+    _argv: *mut *mut raw::RedisModuleString,
+    _argc: c_int,
+) -> c_int {
+    let client = unsafe { raw::RedisModule_GetBlockedClientHandle.unwrap()(ctx) } as usize;
+    let on_timeout = BLOCKED_CLIENT_CALLBACKS
+        .lock()
+        .unwrap()
+        .get_mut(&client)
+        .and_then(|callbacks| callbacks.on_timeout.take());
+    let context = Context::new(ctx);
+    let result = on_timeout.map_or(Ok(RedisValue::Null), |on_timeout| on_timeout(&context));
+    context.reply(result) as c_int
+}
+
+extern "C" fn blocked_client_disconnect(
+    ctx: *mut raw::RedisModuleCtx,
+    bc: *mut raw::RedisModuleBlockedClient,
+) {
+    let on_disconnect = BLOCKED_CLIENT_CALLBACKS
+        .lock()
+        .unwrap()
+        .get_mut(&(bc as usize))
+        .and_then(|callbacks| callbacks.on_disconnect.take());
+    if let Some(on_disconnect) = on_disconnect {
+        on_disconnect(&Context::new(ctx));
+    }
+}
+
+extern "C" fn blocked_client_free<T>(_ctx: *mut raw::RedisModuleCtx, privdata: *mut c_void) {
+    let unblocked = unsafe { Box::from_raw(privdata.cast::<Unblocked<T>>()) };
+    BLOCKED_CLIENT_CALLBACKS
+        .lock()
+        .unwrap()
+        .remove(&unblocked.client);
+}
+
+bitflags! {
+    /// Flags of [`BlockClientOnKeysBuilder::flags`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct BlockOnKeysFlags: c_int {
+        /// Also call the ready callback when one of the keys is deleted, as
+        /// `XREADGROUP` does when its stream is deleted.
+        const UNBLOCK_DELETED = raw::REDISMODULE_BLOCK_UNBLOCK_DELETED as c_int;
+    }
+}
+
+type OnReady<T> = Box<dyn FnMut(&Context, &RedisString, &mut T) -> Option<RedisResult>>;
+type OnTimeout<T> = Box<dyn FnOnce(&Context, &mut T) -> RedisResult>;
+type OnFree<T> = Box<dyn FnOnce(&Context, T)>;
+
+/// The private data of a client blocked on keys, owned by Redis until it is
+/// passed to the free callback.
+struct BlockedOnKeys<T> {
+    privdata: T,
+    on_ready: OnReady<T>,
+    on_timeout: Option<OnTimeout<T>>,
+    on_free: Option<OnFree<T>>,
+}
+
+/// Blocks the client of the current command until one of a set of keys is
+/// signaled as ready, see [`Context::block_client_on_keys`].
+#[must_use]
+pub struct BlockClientOnKeysBuilder<'ctx, T> {
+    ctx: &'ctx Context,
+    keys: Vec<*mut raw::RedisModuleString>,
+    timeout: Duration,
+    flags: BlockOnKeysFlags,
+    state: BlockedOnKeys<T>,
+}
+
+impl<'ctx, T: 'static> BlockClientOnKeysBuilder<'ctx, T> {
+    /// The maximum time to wait for the keys, the client is blocked without
+    /// a timeout by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn flags(mut self, flags: BlockOnKeysFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Called with the private data when the timeout is reached, the result
+    /// is sent to the client. Without it, the client gets a null reply on
+    /// timeout.
+    pub fn on_timeout<F: FnOnce(&Context, &mut T) -> RedisResult + 'static>(
+        mut self,
+        on_timeout: F,
+    ) -> Self {
+        self.state.on_timeout = Some(Box::new(on_timeout));
+        self
+    }
+
+    /// Called with the private data once the client is unblocked, whether
+    /// it was served, timed out or disconnected. Without it, the private
+    /// data is simply dropped.
+    pub fn on_free<F: FnOnce(&Context, T) + 'static>(mut self, on_free: F) -> Self {
+        self.state.on_free = Some(Box::new(on_free));
+        self
+    }
+
+    /// Blocks the client. The command must then return
+    /// [`RedisValue::NoReply`], the reply is sent by the ready or timeout
+    /// callbacks.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_BlockClientOnKeys` is missing in redismodule.h
+    pub fn block(self) -> RedisResult<()> {
+        let timeout = c_longlong::try_from(self.timeout.as_millis())
+            .map_err(|_| RedisError::Str("Blocking timeout is too big"))?;
+        let mut keys = self.keys;
+        let privdata = Box::into_raw(Box::new(self.state)).cast::<c_void>();
+        let blocked_client = unsafe {
+            if self.flags.is_empty() {
+                raw::RedisModule_BlockClientOnKeys.unwrap()(
+                    self.ctx.ctx,
+                    Some(block_on_keys_reply::<T>),
+                    Some(block_on_keys_timeout::<T>),
+                    Some(block_on_keys_free::<T>),
+                    timeout,
+                    keys.as_mut_ptr(),
+                    keys.len() as c_int,
+                    privdata,
+                )
+            } else {
+                let Some(block_client_on_keys) = raw::RedisModule_BlockClientOnKeysWithFlags else {
+                    drop(Box::from_raw(privdata.cast::<BlockedOnKeys<T>>()));
+                    return Err(RedisError::Str(
+                        "RedisModule_BlockClientOnKeysWithFlags does not exists",
+                    ));
+                };
+                block_client_on_keys(
+                    self.ctx.ctx,
+                    Some(block_on_keys_reply::<T>),
+                    Some(block_on_keys_timeout::<T>),
+                    Some(block_on_keys_free::<T>),
+                    timeout,
+                    keys.as_mut_ptr(),
+                    keys.len() as c_int,
+                    privdata,
+                    self.flags.bits(),
+                )
+            }
+        };
+        if blocked_client.is_null() {
+            drop(unsafe { Box::from_raw(privdata.cast::<BlockedOnKeys<T>>()) });
+            return Err(RedisError::Str("Failed blocking the client"));
+        }
+        Ok(())
+    }
+}
+
+fn blocked_on_keys<'a, T>(ctx: *mut raw::RedisModuleCtx) -> &'a mut BlockedOnKeys<T> {
+    unsafe {
+        &mut *raw::RedisModule_GetBlockedClientPrivateData.unwrap()(ctx).cast::<BlockedOnKeys<T>>()
+    }
+}
+
+extern "C" fn block_on_keys_reply<T>(
+    ctx: *mut raw::RedisModuleCtx,
+    _argv: *mut *mut raw::RedisModuleString,
+    _argc: c_int,
+) -> c_int {
+    let context = Context::new(ctx);
+    let Some(key) = context.get_blocked_client_ready_key() else {
+        return raw::Status::Err as c_int;
+    };
+    let state = blocked_on_keys::<T>(ctx);
+    match (state.on_ready)(&context, &key, &mut state.privdata) {
+        Some(result) => {
+            context.reply(result);
+            raw::Status::Ok as c_int
+        }
+        // Keep the client blocked until the next time a key is ready.
+        None => raw::Status::Err as c_int,
+    }
+}
+
+extern "C" fn block_on_keys_timeout<T>(
+    ctx: *mut raw::RedisModuleCtx,
+    _argv: *mut *mut raw::RedisModuleString,
+    _argc: c_int,
+) -> c_int {
+    let context = Context::new(ctx);
+    let state = blocked_on_keys::<T>(ctx);
+    let result = state
+        .on_timeout
+        .take()
+        .map_or(Ok(RedisValue::Null), |on_timeout| {
+            on_timeout(&context, &mut state.privdata)
+        });
+    context.reply(result) as c_int
+}
+
+extern "C" fn block_on_keys_free<T>(ctx: *mut raw::RedisModuleCtx, privdata: *mut c_void) {
+    let state = unsafe { Box::from_raw(privdata.cast::<BlockedOnKeys<T>>()) };
+    if let Some(on_free) = state.on_free {
+        on_free(&Context::new(ctx), state.privdata);
     }
 }
 
@@ -30,6 +448,92 @@ impl Context {
 
         BlockedClient {
             inner: blocked_client,
+            reply: None,
+            with_callbacks: false,
+        }
+    }
+
+    /// Returns a builder blocking the client of the current command until
+    /// the returned [`BlockedClient`] is unblocked with a value, typically
+    /// from another thread. `reply` is then called with the value on the
+    /// main thread, and its result is sent to the client.
+    pub fn block_client_with_reply<T, F>(&self, reply: F) -> BlockClientBuilder<'_, T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Self, T) -> RedisResult + Send + 'static,
+    {
+        BlockClientBuilder {
+            ctx: self,
+            timeout: Duration::ZERO,
+            reply: Box::new(reply),
+            callbacks: BlockedClientCallbacks::default(),
+        }
+    }
+
+    /// Returns `true` when called from the free callback of a blocked
+    /// client that was unblocked because it disconnected.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_BlockedClientDisconnected` is missing in redismodule.h
+    #[must_use]
+    pub fn blocked_client_disconnected(&self) -> bool {
+        unsafe { raw::RedisModule_BlockedClientDisconnected.unwrap()(self.ctx) != 0 }
+    }
+
+    /// Returns a builder blocking the client of the current command until
+    /// one of `keys` is signaled as ready, either by Redis when a list,
+    /// sorted set or stream key is written, or by a module with
+    /// [`Context::signal_key_as_ready`].
+    ///
+    /// Every time one of the keys is ready, `on_ready` is called with the
+    /// ready key and the private data. It returns the reply to unblock the
+    /// client with, or `None` to keep the client blocked, e.g. when the key
+    /// turns out to be empty.
+    pub fn block_client_on_keys<T, F>(
+        &self,
+        keys: &[RedisString],
+        privdata: T,
+        on_ready: F,
+    ) -> BlockClientOnKeysBuilder<'_, T>
+    where
+        T: 'static,
+        F: FnMut(&Self, &RedisString, &mut T) -> Option<RedisResult> + 'static,
+    {
+        BlockClientOnKeysBuilder {
+            ctx: self,
+            // Redis keeps its own reference to the keys.
+            keys: keys.iter().map(|key| key.inner).collect(),
+            timeout: Duration::ZERO,
+            flags: BlockOnKeysFlags::empty(),
+            state: BlockedOnKeys {
+                privdata,
+                on_ready: Box::new(on_ready),
+                on_timeout: None,
+                on_free: None,
+            },
         }
     }
+
+    /// Returns the key that caused the ready callback of a client blocked
+    /// on keys to be called.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetBlockedClientReadyKey` is missing in redismodule.h
+    #[must_use]
+    pub fn get_blocked_client_ready_key(&self) -> Option<RedisString> {
+        let key = unsafe { raw::RedisModule_GetBlockedClientReadyKey.unwrap()(self.ctx) };
+        (!key.is_null()).then(|| RedisString::new(NonNull::new(self.ctx), key))
+    }
+
+    /// Signals that `key` is ready, so that the clients blocked on it with
+    /// [`Context::block_client_on_keys`] are served.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_SignalKeyAsReady` is missing in redismodule.h
+    pub fn signal_key_as_ready(&self, key: &RedisString) {
+        unsafe { raw::RedisModule_SignalKeyAsReady.unwrap()(self.ctx, key.inner) };
+    }
 }
@@ -0,0 +1,109 @@
+use std::ptr;
+
+use redis_module_macros_internals::api;
+
+use crate::context::server_events::ClientInfo;
+use crate::{raw, Context, RedisError, RedisString};
+
+/// Returns the string returned by one of the `GetClient*ById` functions,
+/// which is owned by the caller.
+fn client_string(string: *mut raw::RedisModuleString) -> Option<RedisString> {
+    (!string.is_null()).then(|| RedisString::from_redis_module_string(ptr::null_mut(), string))
+}
+
+impl Context {
+    /// Returns the id of the current client, which is unique for the
+    /// lifetime of the server, or 0 when there is no client, e.g. in a
+    /// timer or a server event.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetClientId` is missing in redismodule.h
+    #[must_use]
+    pub fn client_id(&self) -> u64 {
+        unsafe { raw::RedisModule_GetClientId.unwrap()(self.ctx) }
+    }
+
+    /// Returns the information of the client with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetClientInfoById` is missing in redismodule.h
+    pub fn client_info(&self, client_id: u64) -> Result<ClientInfo, RedisError> {
+        let mut info = raw::RedisModuleClientInfo {
+            version: raw::REDISMODULE_CLIENTINFO_VERSION.into(),
+            flags: 0,
+            id: 0,
+            addr: [0; 46],
+            port: 0,
+            db: 0,
+        };
+        let res: raw::Status = unsafe {
+            raw::RedisModule_GetClientInfoById.unwrap()(ptr::addr_of_mut!(info).cast(), client_id)
+        }
+        .into();
+        match res {
+            raw::Status::Ok => Ok(ClientInfo::from(&info)),
+            raw::Status::Err => Err(RedisError::Str("Client does not exist")),
+        }
+    }
+
+    api!(
+        [RedisModule_GetClientNameById],
+        /// Returns the name of the client with the given id, as set with
+        /// `CLIENT SETNAME`, or `None` if the client has no name or does not
+        /// exist.
+        pub fn client_name(&self, client_id: u64) -> Option<RedisString> {
+            client_string(unsafe { RedisModule_GetClientNameById(self.ctx, client_id) })
+        }
+    );
+
+    api!(
+        [RedisModule_SetClientNameById],
+        /// Sets the name of the client with the given id, as `CLIENT SETNAME`
+        /// does. An empty name removes the name of the client.
+        pub fn set_client_name(
+            &self,
+            client_id: u64,
+            name: &RedisString,
+        ) -> Result<(), RedisError> {
+            let res: raw::Status =
+                unsafe { RedisModule_SetClientNameById(client_id, name.inner) }.into();
+            match res {
+                raw::Status::Ok => Ok(()),
+                raw::Status::Err => Err(RedisError::Str(
+                    "Client does not exist or the name contains spaces or special characters",
+                )),
+            }
+        }
+    );
+
+    /// Returns the name of the ACL user the client with the given id is
+    /// authenticated as.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetClientUserNameById` is missing in redismodule.h
+    pub fn client_username(&self, client_id: u64) -> Result<RedisString, RedisError> {
+        client_string(unsafe {
+            raw::RedisModule_GetClientUserNameById.unwrap()(self.ctx, client_id)
+        })
+        .ok_or(RedisError::Str(
+            "Client does not exist or is not authenticated as an ACL user",
+        ))
+    }
+
+    /// Returns the X.509 certificate the client with the given id used to
+    /// authenticate its TLS connection, in PEM format, or `None` if the
+    /// client did not use one or does not exist.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetClientCertificate` is missing in redismodule.h
+    #[must_use]
+    pub fn client_certificate(&self, client_id: u64) -> Option<RedisString> {
+        client_string(unsafe {
+            raw::RedisModule_GetClientCertificate.unwrap()(self.ctx, client_id)
+        })
+    }
+}
@@ -0,0 +1,329 @@
+use std::fmt;
+use std::os::raw::{c_char, c_int, c_uchar};
+use std::ptr;
+use std::sync::Mutex;
+
+use bitflags::bitflags;
+
+use crate::{raw, Context, RedisError, RedisResult, Status};
+
+const NODE_ID_LEN: usize = raw::REDISMODULE_NODE_ID_LEN as usize;
+
+/// Size of the buffer `RedisModule_GetClusterNodeInfo` copies the node IP
+/// into (`NET_IP_STR_LEN` in the Redis sources).
+const NODE_IP_LEN: usize = 46;
+
+/// The ID of a node of the cluster, 40 hexadecimal characters.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClusterNodeId {
+    // Some of the cluster APIs expect a NUL terminated ID, so one extra byte
+    // is always kept at the end.
+    id: [u8; NODE_ID_LEN + 1],
+}
+
+impl ClusterNodeId {
+    /// Copies a node ID out of a buffer of at least `REDISMODULE_NODE_ID_LEN`
+    /// bytes, as returned by the cluster APIs.
+    unsafe fn from_raw(id: *const c_char) -> Self {
+        let mut res = Self {
+            id: [0; NODE_ID_LEN + 1],
+        };
+        ptr::copy_nonoverlapping(id.cast::<u8>(), res.id.as_mut_ptr(), NODE_ID_LEN);
+        res
+    }
+
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.id[..NODE_ID_LEN]
+    }
+
+    const fn as_ptr(&self) -> *const c_char {
+        self.id.as_ptr().cast::<c_char>()
+    }
+}
+
+impl TryFrom<&str> for ClusterNodeId {
+    type Error = RedisError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value.len() != NODE_ID_LEN || value.contains('\0') {
+            return Err(RedisError::String(format!(
+                "Value {value} is not a valid cluster node id."
+            )));
+        }
+        let mut res = Self {
+            id: [0; NODE_ID_LEN + 1],
+        };
+        res.id[..NODE_ID_LEN].copy_from_slice(value.as_bytes());
+        Ok(res)
+    }
+}
+
+impl fmt::Display for ClusterNodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(self.as_bytes()))
+    }
+}
+
+impl fmt::Debug for ClusterNodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ClusterNodeId")
+            .field(&String::from_utf8_lossy(self.as_bytes()))
+            .finish()
+    }
+}
+
+bitflags! {
+    /// The flags of a cluster node, see [`ClusterNodeInfo`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ClusterNodeFlags: c_int {
+        /// The node is the current node.
+        const MYSELF = raw::REDISMODULE_NODE_MYSELF as c_int;
+        /// The node is a master.
+        const MASTER = raw::REDISMODULE_NODE_MASTER as c_int;
+        /// The node is a replica.
+        const SLAVE = raw::REDISMODULE_NODE_SLAVE as c_int;
+        /// The node is seen as failing by the current node.
+        const PFAIL = raw::REDISMODULE_NODE_PFAIL as c_int;
+        /// The cluster agrees the node is failing.
+        const FAIL = raw::REDISMODULE_NODE_FAIL as c_int;
+        /// The replica is configured to never fail over.
+        const NOFAILOVER = raw::REDISMODULE_NODE_NOFAILOVER as c_int;
+    }
+}
+
+bitflags! {
+    /// Flags changing the behavior of Redis Cluster, see
+    /// [`Context::set_cluster_flags`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ClusterFlags: u64 {
+        /// Prevent automatic failover of the replicas of this node.
+        const NO_FAILOVER = raw::REDISMODULE_CLUSTER_FLAG_NO_FAILOVER as u64;
+        /// Disable the redirection of clients to other nodes, the module is
+        /// then in charge of the keys distribution.
+        const NO_REDIRECTION = raw::REDISMODULE_CLUSTER_FLAG_NO_REDIRECTION as u64;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterNodeInfo {
+    pub ip: String,
+    pub port: u16,
+    pub flags: ClusterNodeFlags,
+    /// The master of the node when the node is a replica.
+    pub master_id: Option<ClusterNodeId>,
+}
+
+type ClusterMessageReceiver = Box<dyn FnMut(&Context, &ClusterNodeId, u8, &[u8]) + Send>;
+
+/// The cluster message receivers, indexed by message type. A receiver is
+/// taken out of its slot while it runs, so that it can register or
+/// unregister receivers itself.
+static CLUSTER_MESSAGE_RECEIVERS: Mutex<Vec<(u8, Option<ClusterMessageReceiver>)>> =
+    Mutex::new(Vec::new());
+
+extern "C" fn cluster_message_receiver(
+    ctx: *mut raw::RedisModuleCtx,
+    sender_id: *const c_char,
+    msg_type: u8,
+    payload: *const c_uchar,
+    len: u32,
+) {
+    let take_receiver = || {
+        CLUSTER_MESSAGE_RECEIVERS
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|(t, _)| *t == msg_type)
+            .and_then(|(_, receiver)| receiver.take())
+    };
+    let Some(mut receiver) = take_receiver() else {
+        return;
+    };
+
+    let context = Context::new(ctx);
+    let sender = unsafe { ClusterNodeId::from_raw(sender_id) };
+    let payload = if payload.is_null() {
+        &[][..]
+    } else {
+        unsafe { std::slice::from_raw_parts(payload, len as usize) }
+    };
+    receiver(&context, &sender, msg_type, payload);
+
+    // Put the receiver back, unless it was replaced or unregistered meanwhile.
+    let mut receivers = CLUSTER_MESSAGE_RECEIVERS.lock().unwrap();
+    if let Some((_, slot @ None)) = receivers.iter_mut().find(|(t, _)| *t == msg_type) {
+        *slot = Some(receiver);
+    }
+}
+
+impl Context {
+    /// Registers `receiver` to be called for every message of type
+    /// `msg_type` sent by other nodes of the cluster with
+    /// [`Context::send_cluster_message`]. The receiver is given the ID of
+    /// the sending node, the message type and the payload.
+    ///
+    /// Registering a receiver for a type that already has one replaces it.
+    /// This is a no-op when cluster mode is disabled.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_RegisterClusterMessageReceiver` is missing in redismodule.h
+    pub fn register_cluster_message_receiver<
+        F: FnMut(&Self, &ClusterNodeId, u8, &[u8]) + Send + 'static,
+    >(
+        &self,
+        msg_type: u8,
+        receiver: F,
+    ) {
+        let mut receivers = CLUSTER_MESSAGE_RECEIVERS.lock().unwrap();
+        receivers.retain(|(t, _)| *t != msg_type);
+        receivers.push((msg_type, Some(Box::new(receiver))));
+        unsafe {
+            raw::RedisModule_RegisterClusterMessageReceiver.unwrap()(
+                self.ctx,
+                msg_type,
+                Some(cluster_message_receiver),
+            );
+        }
+    }
+
+    /// Removes the receiver of the messages of type `msg_type`, if any.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_RegisterClusterMessageReceiver` is missing in redismodule.h
+    pub fn unregister_cluster_message_receiver(&self, msg_type: u8) {
+        CLUSTER_MESSAGE_RECEIVERS
+            .lock()
+            .unwrap()
+            .retain(|(t, _)| *t != msg_type);
+        unsafe {
+            raw::RedisModule_RegisterClusterMessageReceiver.unwrap()(self.ctx, msg_type, None);
+        }
+    }
+
+    /// Sends a message of type `msg_type` to the node `target`, or to all
+    /// the nodes of the cluster when `target` is `None`. The message is
+    /// handled by the receiver registered for that type on the target node,
+    /// see [`Context::register_cluster_message_receiver`].
+    ///
+    /// Fails when cluster mode is disabled or the target node is unknown.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_SendClusterMessage` is missing in redismodule.h
+    pub fn send_cluster_message(
+        &self,
+        target: Option<&ClusterNodeId>,
+        msg_type: u8,
+        payload: &[u8],
+    ) -> RedisResult<()> {
+        let len = u32::try_from(payload.len())
+            .map_err(|_| RedisError::Str("Cluster message payload is too big"))?;
+        let status: Status = unsafe {
+            raw::RedisModule_SendClusterMessage.unwrap()(
+                self.ctx,
+                target.map_or(ptr::null(), ClusterNodeId::as_ptr),
+                msg_type,
+                payload.as_ptr().cast::<c_char>(),
+                len,
+            )
+        }
+        .into();
+        match status {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::Str("Failed sending cluster message")),
+        }
+    }
+
+    /// Returns the IDs of all the nodes of the cluster, or `None` when
+    /// cluster mode is disabled.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetClusterNodesList` is missing in redismodule.h
+    #[must_use]
+    pub fn get_cluster_nodes_list(&self) -> Option<Vec<ClusterNodeId>> {
+        let mut num_nodes = 0;
+        let ids =
+            unsafe { raw::RedisModule_GetClusterNodesList.unwrap()(self.ctx, &mut num_nodes) };
+        if ids.is_null() {
+            return None;
+        }
+        let res = unsafe { std::slice::from_raw_parts(ids, num_nodes) }
+            .iter()
+            .map(|id| unsafe { ClusterNodeId::from_raw(*id) })
+            .collect();
+        unsafe { raw::RedisModule_FreeClusterNodesList.unwrap()(ids) };
+        Some(res)
+    }
+
+    /// Returns the information of the node `id`, or `None` if the node is
+    /// unknown or cluster mode is disabled.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetClusterNodeInfo` is missing in redismodule.h
+    #[must_use]
+    pub fn get_cluster_node_info(&self, id: &ClusterNodeId) -> Option<ClusterNodeInfo> {
+        let mut ip = [0u8; NODE_IP_LEN + 1];
+        let mut master_id = [0u8; NODE_ID_LEN];
+        let mut port: c_int = 0;
+        let mut flags: c_int = 0;
+        let res = unsafe {
+            raw::RedisModule_GetClusterNodeInfo.unwrap()(
+                self.ctx,
+                id.as_ptr(),
+                ip.as_mut_ptr().cast::<c_char>(),
+                master_id.as_mut_ptr().cast::<c_char>(),
+                &mut port,
+                &mut flags,
+            )
+        };
+        if Status::Err == res.into() {
+            return None;
+        }
+        let ip_len = ip.iter().position(|c| *c == 0).unwrap_or(NODE_IP_LEN);
+        // The master ID is zeroed when the node has no known master.
+        let master_id = master_id
+            .iter()
+            .any(|c| *c != 0)
+            .then(|| unsafe { ClusterNodeId::from_raw(master_id.as_ptr().cast::<c_char>()) });
+        Some(ClusterNodeInfo {
+            ip: String::from_utf8_lossy(&ip[..ip_len]).into_owned(),
+            port: port as u16,
+            flags: ClusterNodeFlags::from_bits_truncate(flags),
+            master_id,
+        })
+    }
+
+    /// Returns the ID of the current node, or `None` when cluster mode is
+    /// disabled.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetMyClusterID` is missing in redismodule.h
+    #[must_use]
+    pub fn get_my_cluster_id(&self) -> Option<ClusterNodeId> {
+        let id = unsafe { raw::RedisModule_GetMyClusterID.unwrap()() };
+        (!id.is_null()).then(|| unsafe { ClusterNodeId::from_raw(id) })
+    }
+
+    /// Returns the number of nodes of the cluster, `0` when cluster mode is
+    /// disabled.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetClusterSize` is missing in redismodule.h
+    #[must_use]
+    pub fn get_cluster_size(&self) -> usize {
+        unsafe { raw::RedisModule_GetClusterSize.unwrap()() }
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_SetClusterFlags` is missing in redismodule.h
+    pub fn set_cluster_flags(&self, flags: ClusterFlags) {
+        unsafe { raw::RedisModule_SetClusterFlags.unwrap()(self.ctx, flags.bits()) };
+    }
+}
@@ -0,0 +1,262 @@
+use std::os::raw::c_int;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+use bitflags::bitflags;
+
+use crate::{raw, Context, RedisError, RedisResult, RedisString, Status, MODULE_CONTEXT};
+
+bitflags! {
+    pub struct CommandFilterFlags: c_int {
+        /// Do not run the filter on commands executed by the module itself
+        /// through `RedisModule_Call`.
+        const NOSELF = raw::REDISMODULE_CMDFILTER_NOSELF as c_int;
+    }
+}
+
+/// The command being filtered, passed to the filter callback. The arguments
+/// include the command name at position 0.
+pub struct CommandFilterCtx {
+    inner: *mut raw::RedisModuleCommandFilterCtx,
+}
+
+impl CommandFilterCtx {
+    #[must_use]
+    pub const fn new(inner: *mut raw::RedisModuleCommandFilterCtx) -> Self {
+        Self { inner }
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_CommandFilterArgsCount` is missing in redismodule.h
+    #[must_use]
+    pub fn args_count(&self) -> usize {
+        unsafe { raw::RedisModule_CommandFilterArgsCount.unwrap()(self.inner) as usize }
+    }
+
+    /// Returns the argument at position `pos`, or `None` if `pos` is out of
+    /// range.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_CommandFilterArgGet` is missing in redismodule.h
+    #[must_use]
+    pub fn arg_get(&self, pos: usize) -> Option<RedisString> {
+        let arg =
+            unsafe { raw::RedisModule_CommandFilterArgGet.unwrap()(self.inner, pos as c_int) };
+        if arg.is_null() {
+            return None;
+        }
+        // The argument is owned by the filtered command, retain it so it can
+        // outlive the filter callback.
+        Some(RedisString::new(None, arg))
+    }
+
+    /// Returns all the arguments of the filtered command.
+    #[must_use]
+    pub fn args(&self) -> Vec<RedisString> {
+        (0..self.args_count())
+            .filter_map(|pos| self.arg_get(pos))
+            .collect()
+    }
+
+    /// Inserts `arg` at position `pos`, shifting the following arguments.
+    ///
+    /// Redis takes ownership of the argument, so it must not be created with
+    /// a context that uses automatic memory management.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_CommandFilterArgInsert` is missing in redismodule.h
+    pub fn arg_insert(&mut self, pos: usize, arg: RedisString) -> RedisResult<()> {
+        // Redis does not take the argument when the position is out of
+        // range, keep it so it is freed.
+        if pos > self.args_count() {
+            return Status::Err.into();
+        }
+        let status: Status = unsafe {
+            raw::RedisModule_CommandFilterArgInsert.unwrap()(self.inner, pos as c_int, arg.take())
+        }
+        .into();
+        status.into()
+    }
+
+    /// Replaces the argument at position `pos` with `arg`.
+    ///
+    /// Redis takes ownership of the argument, see [`Self::arg_insert`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_CommandFilterArgReplace` is missing in redismodule.h
+    pub fn arg_replace(&mut self, pos: usize, arg: RedisString) -> RedisResult<()> {
+        if pos >= self.args_count() {
+            return Status::Err.into();
+        }
+        let status: Status = unsafe {
+            raw::RedisModule_CommandFilterArgReplace.unwrap()(self.inner, pos as c_int, arg.take())
+        }
+        .into();
+        status.into()
+    }
+
+    /// Deletes the argument at position `pos`, shifting the following
+    /// arguments.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_CommandFilterArgDelete` is missing in redismodule.h
+    pub fn arg_delete(&mut self, pos: usize) -> RedisResult<()> {
+        let status: Status =
+            unsafe { raw::RedisModule_CommandFilterArgDelete.unwrap()(self.inner, pos as c_int) }
+                .into();
+        status.into()
+    }
+}
+
+type FilterCallback = Box<dyn FnMut(&mut CommandFilterCtx) + Send>;
+
+/// The maximal number of command filters a module may register at a time.
+pub const MAX_COMMAND_FILTERS: usize = 16;
+
+/// The registered filter callbacks. The Redis filter callback does not take
+/// any private data, so each slot gets its own trampoline which calls the
+/// callback stored in it. The slots are not locked while the callback runs,
+/// so it may register and unregister filters, including itself.
+static COMMAND_FILTERS: [AtomicPtr<FilterCallback>; MAX_COMMAND_FILTERS] =
+    [const { AtomicPtr::new(ptr::null_mut()) }; MAX_COMMAND_FILTERS];
+
+/// The number of running calls of the callback of each slot, filters run
+/// again on the commands the callback calls.
+static RUNNING_FILTERS: [AtomicUsize; MAX_COMMAND_FILTERS] =
+    [const { AtomicUsize::new(0) }; MAX_COMMAND_FILTERS];
+
+/// Whether the filter of each slot was unregistered while its callback was
+/// running, the callback is then freed once it returns.
+static RELEASED_FILTERS: [AtomicBool; MAX_COMMAND_FILTERS] =
+    [const { AtomicBool::new(false) }; MAX_COMMAND_FILTERS];
+
+extern "C" fn command_filter_callback<const SLOT: usize>(
+    fctx: *mut raw::RedisModuleCommandFilterCtx,
+) {
+    let callback = COMMAND_FILTERS[SLOT].load(Ordering::Acquire);
+    if callback.is_null() || RELEASED_FILTERS[SLOT].load(Ordering::Acquire) {
+        return;
+    }
+    RUNNING_FILTERS[SLOT].fetch_add(1, Ordering::AcqRel);
+    // The callback is not freed while it runs, see `release_command_filter_slot`.
+    unsafe { (*callback)(&mut CommandFilterCtx::new(fctx)) };
+    if RUNNING_FILTERS[SLOT].fetch_sub(1, Ordering::AcqRel) == 1
+        && RELEASED_FILTERS[SLOT].swap(false, Ordering::AcqRel)
+    {
+        free_command_filter_slot(SLOT);
+    }
+}
+
+type FilterTrampoline = extern "C" fn(*mut raw::RedisModuleCommandFilterCtx);
+
+static COMMAND_FILTER_TRAMPOLINES: [FilterTrampoline; MAX_COMMAND_FILTERS] = [
+    command_filter_callback::<0>,
+    command_filter_callback::<1>,
+    command_filter_callback::<2>,
+    command_filter_callback::<3>,
+    command_filter_callback::<4>,
+    command_filter_callback::<5>,
+    command_filter_callback::<6>,
+    command_filter_callback::<7>,
+    command_filter_callback::<8>,
+    command_filter_callback::<9>,
+    command_filter_callback::<10>,
+    command_filter_callback::<11>,
+    command_filter_callback::<12>,
+    command_filter_callback::<13>,
+    command_filter_callback::<14>,
+    command_filter_callback::<15>,
+];
+
+/// Frees the callback of an unregistered filter, or defers it until the
+/// callback returns if the filter was unregistered from its own callback.
+fn release_command_filter_slot(slot: usize) {
+    if RUNNING_FILTERS[slot].load(Ordering::Acquire) > 0 {
+        RELEASED_FILTERS[slot].store(true, Ordering::Release);
+    } else {
+        free_command_filter_slot(slot);
+    }
+}
+
+/// Takes the callback out of the slot and frees it.
+fn free_command_filter_slot(slot: usize) {
+    let callback = COMMAND_FILTERS[slot].swap(ptr::null_mut(), Ordering::AcqRel);
+    if !callback.is_null() {
+        drop(unsafe { Box::from_raw(callback) });
+    }
+}
+
+/// A registered command filter, unregistered when dropped.
+///
+/// Use [`CommandFilter::leak`] to keep the filter for the lifetime of the
+/// module.
+pub struct CommandFilter {
+    inner: *mut raw::RedisModuleCommandFilter,
+    slot: usize,
+}
+
+impl CommandFilter {
+    /// Keeps the filter registered until the module is unloaded.
+    pub fn leak(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for CommandFilter {
+    fn drop(&mut self) {
+        // Filters can be unregistered with any context of the module, the
+        // one the filter was registered with may be gone by now.
+        let ctx = MODULE_CONTEXT.ctx.load(Ordering::Relaxed);
+        unsafe { raw::RedisModule_UnregisterCommandFilter.unwrap()(ctx, self.inner) };
+        release_command_filter_slot(self.slot);
+    }
+}
+
+impl Context {
+    /// Registers `callback` to be called on every command executed by Redis,
+    /// before the command itself runs. The callback may inspect and rewrite
+    /// the arguments of the command through the given [`CommandFilterCtx`].
+    ///
+    /// At most [`MAX_COMMAND_FILTERS`] filters may be registered at a time.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_RegisterCommandFilter` is missing in redismodule.h
+    pub fn register_command_filter<F: FnMut(&mut CommandFilterCtx) + Send + 'static>(
+        &self,
+        flags: CommandFilterFlags,
+        callback: F,
+    ) -> Result<CommandFilter, RedisError> {
+        let callback: *mut FilterCallback = Box::into_raw(Box::new(Box::new(callback)));
+        let slot = COMMAND_FILTERS.iter().position(|slot| {
+            slot.compare_exchange(
+                ptr::null_mut(),
+                callback,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+        });
+        let Some(slot) = slot else {
+            drop(unsafe { Box::from_raw(callback) });
+            return Err(RedisError::Str("Too many command filters are registered"));
+        };
+        let inner = unsafe {
+            raw::RedisModule_RegisterCommandFilter.unwrap()(
+                self.ctx,
+                Some(COMMAND_FILTER_TRAMPOLINES[slot]),
+                flags.bits(),
+            )
+        };
+        if inner.is_null() {
+            free_command_filter_slot(slot);
+            return Err(RedisError::Str("Failed registering command filter"));
+        }
+        Ok(CommandFilter { inner, slot })
+    }
+}
@@ -1,4 +1,5 @@
 use crate::raw;
+use crate::AclChannelPermissions;
 use crate::Context;
 use crate::RedisError;
 use crate::Status;
@@ -6,6 +7,7 @@ use bitflags::bitflags;
 use libc::c_char;
 use linkme::distributed_slice;
 use redis_module_macros_internals::api;
+use std::collections::HashSet;
 use std::ffi::CString;
 use std::mem::MaybeUninit;
 use std::os::raw::c_int;
@@ -308,43 +310,308 @@ impl From<&KeySpec> for raw::RedisModuleCommandKeySpec {
     }
 }
 
+/// A struct that specify which arguments of a command are pubsub channels,
+/// and how the command uses them. Redis asks the command for its channels
+/// when checking the ACL channel permissions of the user.
+/// * `first` - Index of the first channel.
+/// * `last` - Index of the last channel. Can be negative, in which case
+///   -1 indicates the last argument, -2 one before the last and so on.
+/// * `step` - How many arguments should we skip after finding a channel,
+///   in order to find the next one.
+pub struct ChannelSpec {
+    flags: AclChannelPermissions,
+    first: i32,
+    last: i32,
+    step: i32,
+}
+
+impl ChannelSpec {
+    pub fn new(flags: AclChannelPermissions, first: i32, last: i32, step: i32) -> ChannelSpec {
+        ChannelSpec {
+            flags,
+            first,
+            last,
+            step,
+        }
+    }
+
+    /// Reports the channels of the command, called with `argc` arguments,
+    /// using [`Context::channel_at_pos`].
+    pub fn report_channels(&self, ctx: &Context, argc: i32) {
+        let last = if self.last < 0 {
+            argc + self.last
+        } else {
+            self.last.min(argc - 1)
+        };
+        (self.first..=last)
+            .step_by(self.step.max(1) as usize)
+            .for_each(|pos| ctx.channel_at_pos(pos, &self.flags));
+    }
+}
+
+bitflags! {
+    /// Command argument flags, describing how an argument may appear in the
+    /// command.
+    pub struct CommandArgFlags : u32 {
+        /// The argument is optional.
+        const OPTIONAL = raw::REDISMODULE_CMD_ARG_OPTIONAL;
+
+        /// The argument may repeat itself.
+        const MULTIPLE = raw::REDISMODULE_CMD_ARG_MULTIPLE;
+
+        /// The argument may repeat itself, and so does its token.
+        const MULTIPLE_TOKEN = raw::REDISMODULE_CMD_ARG_MULTIPLE_TOKEN;
+    }
+}
+
+impl TryFrom<&str> for CommandArgFlags {
+    type Error = RedisError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "optional" => Ok(CommandArgFlags::OPTIONAL),
+            "multiple" => Ok(CommandArgFlags::MULTIPLE),
+            "multiple_token" => Ok(CommandArgFlags::MULTIPLE_TOKEN),
+            _ => Err(RedisError::String(format!(
+                "Value {value} is not a valid command argument flag."
+            ))),
+        }
+    }
+}
+
+impl From<Vec<CommandArgFlags>> for CommandArgFlags {
+    fn from(value: Vec<CommandArgFlags>) -> Self {
+        value
+            .into_iter()
+            .fold(CommandArgFlags::empty(), |a, item| a | item)
+    }
+}
+
+/// The type of a command argument, as shown by `COMMAND DOCS`.
+pub enum CommandArgType {
+    String,
+    Integer,
+    Double,
+    /// A key name, `key_spec_index` of the argument is the index of the
+    /// key spec which covers it.
+    Key,
+    Pattern,
+    UnixTime,
+    /// A token without a value, e.g. `NX`.
+    PureToken,
+    /// Exactly one of the sub arguments.
+    OneOf,
+    /// A block of sub arguments which appear together.
+    Block,
+}
+
+impl From<&CommandArgType> for raw::RedisModuleCommandArgType {
+    fn from(value: &CommandArgType) -> Self {
+        match value {
+            CommandArgType::String => raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_STRING,
+            CommandArgType::Integer => raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_INTEGER,
+            CommandArgType::Double => raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_DOUBLE,
+            CommandArgType::Key => raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_KEY,
+            CommandArgType::Pattern => raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_PATTERN,
+            CommandArgType::UnixTime => {
+                raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_UNIX_TIME
+            }
+            CommandArgType::PureToken => {
+                raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_PURE_TOKEN
+            }
+            CommandArgType::OneOf => raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_ONEOF,
+            CommandArgType::Block => raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_BLOCK,
+        }
+    }
+}
+
+/// A struct that describes an argument of a command, shown by
+/// `COMMAND DOCS` and used by clients such as `redis-cli` for hints.
+/// * `name` - Name of the argument.
+/// * `arg_type` - Type of the argument.
+/// * `key_spec_index` - For [`CommandArgType::Key`], the index of the
+///   key spec of the command which covers the argument.
+/// * `token` - The token preceding the argument, e.g. `COUNT`.
+/// * `summary` - A short description of the argument.
+/// * `since` - The first version which included the argument.
+/// * `flags` - How the argument may appear in the command.
+/// * `deprecated_since` - The first version which deprecated the argument.
+/// * `subargs` - The sub arguments of [`CommandArgType::OneOf`] and
+///   [`CommandArgType::Block`] arguments.
+/// * `display_text` - The text shown instead of the name by clients.
+pub struct CommandArg {
+    name: String,
+    arg_type: CommandArgType,
+    key_spec_index: Option<i32>,
+    token: Option<String>,
+    summary: Option<String>,
+    since: Option<String>,
+    flags: CommandArgFlags,
+    deprecated_since: Option<String>,
+    subargs: Vec<CommandArg>,
+    display_text: Option<String>,
+}
+
+impl CommandArg {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: String,
+        arg_type: CommandArgType,
+        key_spec_index: Option<i32>,
+        token: Option<String>,
+        summary: Option<String>,
+        since: Option<String>,
+        flags: CommandArgFlags,
+        deprecated_since: Option<String>,
+        subargs: Vec<CommandArg>,
+        display_text: Option<String>,
+    ) -> CommandArg {
+        CommandArg {
+            name,
+            arg_type,
+            key_spec_index,
+            token,
+            summary,
+            since,
+            flags,
+            deprecated_since,
+            subargs,
+            display_text,
+        }
+    }
+}
+
+/// An entry of the history of a command, shown by `COMMAND DOCS`.
+/// * `since` - The version of the change.
+/// * `changes` - A description of the change.
+pub struct CommandHistoryEntry {
+    since: String,
+    changes: String,
+}
+
+impl CommandHistoryEntry {
+    pub fn new(since: String, changes: String) -> CommandHistoryEntry {
+        CommandHistoryEntry { since, changes }
+    }
+}
+
+/// Holds the strings and the arrays pointed to by the arguments and the
+/// history of a [`raw::RedisModuleCommandInfo`] until it is set, Redis
+/// copies them.
+#[derive(Default)]
+struct CommandDocsStorage {
+    strings: Vec<CString>,
+    args: Vec<Vec<raw::RedisModuleCommandArg>>,
+    history: Vec<raw::RedisModuleCommandHistoryEntry>,
+}
+
+impl CommandDocsStorage {
+    fn str(&mut self, value: Option<&str>) -> *const c_char {
+        value.map_or(ptr::null(), |value| {
+            let value = CString::new(value).unwrap();
+            let ptr = value.as_ptr();
+            self.strings.push(value);
+            ptr
+        })
+    }
+
+    /// Returns the null terminated array of the arguments, or null if
+    /// there are none.
+    fn args(&mut self, args: &[CommandArg]) -> *mut raw::RedisModuleCommandArg {
+        if args.is_empty() {
+            return ptr::null_mut();
+        }
+        let mut raw_args: Vec<raw::RedisModuleCommandArg> = args
+            .iter()
+            .map(|arg| raw::RedisModuleCommandArg {
+                name: self.str(Some(arg.name.as_str())),
+                r#type: (&arg.arg_type).into(),
+                key_spec_index: arg.key_spec_index.unwrap_or(-1),
+                token: self.str(arg.token.as_deref()),
+                summary: self.str(arg.summary.as_deref()),
+                since: self.str(arg.since.as_deref()),
+                flags: arg.flags.bits() as c_int,
+                deprecated_since: self.str(arg.deprecated_since.as_deref()),
+                subargs: self.args(&arg.subargs),
+                display_text: self.str(arg.display_text.as_deref()),
+            })
+            .collect();
+        raw_args.push(unsafe { MaybeUninit::zeroed().assume_init() });
+        let ptr = raw_args.as_mut_ptr();
+        self.args.push(raw_args);
+        ptr
+    }
+
+    /// Returns the null terminated array of the history entries, or null
+    /// if there are none.
+    fn history(
+        &mut self,
+        history: &[CommandHistoryEntry],
+    ) -> *mut raw::RedisModuleCommandHistoryEntry {
+        if history.is_empty() {
+            return ptr::null_mut();
+        }
+        self.history = history
+            .iter()
+            .map(|entry| raw::RedisModuleCommandHistoryEntry {
+                since: self.str(Some(entry.since.as_str())),
+                changes: self.str(Some(entry.changes.as_str())),
+            })
+            .collect();
+        self.history
+            .push(unsafe { MaybeUninit::zeroed().assume_init() });
+        self.history.as_mut_ptr()
+    }
+}
+
 type CommandCallback =
     extern "C" fn(*mut raw::RedisModuleCtx, *mut *mut raw::RedisModuleString, i32) -> i32;
 
 /// A struct represent a CommandInfo
 pub struct CommandInfo {
     name: String,
+    parent: Option<String>,
     flags: Option<String>,
     summary: Option<String>,
     complexity: Option<String>,
     since: Option<String>,
+    history: Vec<CommandHistoryEntry>,
     tips: Option<String>,
+    acl_categories: Option<String>,
     arity: i64,
     key_spec: Vec<KeySpec>,
+    args: Vec<CommandArg>,
     callback: CommandCallback,
 }
 
 impl CommandInfo {
     pub fn new(
         name: String,
+        parent: Option<String>,
         flags: Option<String>,
         summary: Option<String>,
         complexity: Option<String>,
         since: Option<String>,
+        history: Vec<CommandHistoryEntry>,
         tips: Option<String>,
+        acl_categories: Option<String>,
         arity: i64,
         key_spec: Vec<KeySpec>,
+        args: Vec<CommandArg>,
         callback: CommandCallback,
     ) -> CommandInfo {
         CommandInfo {
             name,
+            parent,
             flags,
             summary,
             complexity,
             since,
+            history,
             tips,
+            acl_categories,
             arity,
             key_spec,
+            args,
             callback,
         }
     }
@@ -361,15 +628,73 @@ pub fn get_redis_key_spec(key_spec: Vec<KeySpec>) -> Vec<raw::RedisModuleCommand
     redis_key_spec
 }
 
+/// Sets the space separated ACL categories of a registered command.
+fn set_command_acl_categories(
+    command: *mut raw::RedisModuleCommand,
+    command_name: &str,
+    acl_categories: &str,
+) -> Result<(), RedisError> {
+    let Some(set_command_acl_categories) = (unsafe { raw::RedisModule_SetCommandACLCategories })
+    else {
+        return Err(RedisError::Str(
+            "RedisModule_SetCommandACLCategories does not exists",
+        ));
+    };
+    let acl_categories_c = CString::new(acl_categories)
+        .map_err(|_| RedisError::Str("ACL categories contain a nul byte"))?;
+    if unsafe { set_command_acl_categories(command, acl_categories_c.as_ptr()) }
+        == raw::Status::Err as i32
+    {
+        return Err(RedisError::String(format!(
+            "Failed setting ACL categories {acl_categories} for command {command_name}."
+        )));
+    }
+    Ok(())
+}
+
+/// Sets the space separated ACL categories, e.g. `"read fast"`, of a
+/// command of the module, which was registered using `redis_command!`.
+/// The commands registered using the `command` proc macro declare their
+/// ACL categories directly.
+pub fn set_acl_categories(
+    ctx: &Context,
+    command_name: &str,
+    acl_categories: &str,
+) -> Result<(), RedisError> {
+    let Some(get_command) = (unsafe { raw::RedisModule_GetCommand }) else {
+        return Err(RedisError::Str("RedisModule_GetCommand does not exists"));
+    };
+    let name = CString::new(command_name)
+        .map_err(|_| RedisError::Str("Command name contains a nul byte"))?;
+    let command = unsafe { get_command(ctx.ctx, name.as_ptr()) };
+    if command.is_null() {
+        return Err(RedisError::String(format!(
+            "Failed finding command {command_name}."
+        )));
+    }
+    set_command_acl_categories(command, command_name, acl_categories)
+}
+
 api! {[
         RedisModule_CreateCommand,
+        RedisModule_CreateSubcommand,
         RedisModule_GetCommand,
         RedisModule_SetCommandInfo,
     ],
     /// Register all the commands located on `COMMNADS_LIST`.
+    /// Commands are registered before subcommands, the parents of the
+    /// subcommands are registered as container commands, which only have
+    /// subcommands. Redis does not allow subcommands of a command which has
+    /// a handler, so a parent which is one of the commands is an error.
     fn register_commands_internal(ctx: &Context) -> Result<(), RedisError> {
-        COMMANDS_LIST.iter().try_for_each(|command| {
-            let command_info = command()?;
+        let mut commands = COMMANDS_LIST
+            .iter()
+            .map(|command| command())
+            .collect::<Result<Vec<_>, RedisError>>()?;
+        commands.sort_by_key(|command_info| command_info.parent.is_some());
+        let mut containers = HashSet::new();
+
+        commands.into_iter().try_for_each(|command_info| {
             let name: CString = CString::new(command_info.name.as_str()).unwrap();
             let flags = CString::new(
                 command_info
@@ -379,31 +704,74 @@ api! {[
             )
             .unwrap();
 
-            if unsafe {
-                RedisModule_CreateCommand(
-                    ctx.ctx,
-                    name.as_ptr(),
-                    Some(command_info.callback),
-                    flags.as_ptr(),
-                    0,
-                    0,
-                    0,
-                )
-            } == raw::Status::Err as i32
-            {
+            let (full_name, res) = match command_info.parent.as_deref() {
+                None => (command_info.name.clone(), unsafe {
+                    RedisModule_CreateCommand(
+                        ctx.ctx,
+                        name.as_ptr(),
+                        Some(command_info.callback),
+                        flags.as_ptr(),
+                        0,
+                        0,
+                        0,
+                    )
+                }),
+                Some(parent_name) => {
+                    let parent_name_c = CString::new(parent_name).unwrap();
+                    let mut parent = unsafe { RedisModule_GetCommand(ctx.ctx, parent_name_c.as_ptr()) };
+                    if !parent.is_null() && !containers.contains(parent_name) {
+                        return Err(RedisError::String(format!(
+                            "Parent command {parent_name} has a handler, subcommands can only be added to container commands."
+                        )));
+                    }
+                    if parent.is_null() {
+                        let no_flags = CString::new("").unwrap();
+                        if unsafe {
+                            RedisModule_CreateCommand(
+                                ctx.ctx,
+                                parent_name_c.as_ptr(),
+                                None,
+                                no_flags.as_ptr(),
+                                0,
+                                0,
+                                0,
+                            )
+                        } == raw::Status::Err as i32
+                        {
+                            return Err(RedisError::String(format!(
+                                "Failed register container command {parent_name}."
+                            )));
+                        }
+                        parent = unsafe { RedisModule_GetCommand(ctx.ctx, parent_name_c.as_ptr()) };
+                        containers.insert(parent_name.to_owned());
+                    }
+                    (format!("{parent_name}|{}", command_info.name), unsafe {
+                        RedisModule_CreateSubcommand(
+                            parent,
+                            name.as_ptr(),
+                            Some(command_info.callback),
+                            flags.as_ptr(),
+                            0,
+                            0,
+                            0,
+                        )
+                    })
+                }
+            };
+
+            if res == raw::Status::Err as i32 {
                 return Err(RedisError::String(format!(
-                    "Failed register command {}.",
-                    command_info.name
+                    "Failed register command {full_name}."
                 )));
             }
 
             // Register the extra data of the command
-            let command = unsafe { RedisModule_GetCommand(ctx.ctx, name.as_ptr()) };
+            let full_name_c = CString::new(full_name.as_str()).unwrap();
+            let command = unsafe { RedisModule_GetCommand(ctx.ctx, full_name_c.as_ptr()) };
 
             if command.is_null() {
                 return Err(RedisError::String(format!(
-                    "Failed finding command {} after registration.",
-                    command_info.name
+                    "Failed finding command {full_name} after registration."
                 )));
             }
 
@@ -429,23 +797,25 @@ api! {[
                 .unwrap_or(None);
 
             let key_specs = get_redis_key_spec(command_info.key_spec);
+            let mut docs_storage = CommandDocsStorage::default();
+            let history = docs_storage.history(&command_info.history);
+            let args = docs_storage.args(&command_info.args);
 
             let mut redis_command_info = raw::RedisModuleCommandInfo {
                 version: &COMMNAD_INFO_VERSION,
                 summary: summary.as_ref().map(|v| v.as_ptr()).unwrap_or(ptr::null_mut()),
                 complexity: complexity.as_ref().map(|v| v.as_ptr()).unwrap_or(ptr::null_mut()),
                 since: since.as_ref().map(|v| v.as_ptr()).unwrap_or(ptr::null_mut()),
-                history: ptr::null_mut(), // currently we will not support history
+                history,
                 tips: tips.as_ref().map(|v| v.as_ptr()).unwrap_or(ptr::null_mut()),
                 arity: command_info.arity as c_int,
                 key_specs: key_specs.as_ptr() as *mut raw::RedisModuleCommandKeySpec,
-                args: ptr::null_mut(),
+                args,
             };
 
             if unsafe { RedisModule_SetCommandInfo(command, &mut redis_command_info as *mut raw::RedisModuleCommandInfo) } == raw::Status::Err as i32 {
                 return Err(RedisError::String(format!(
-                    "Failed setting info for command {}.",
-                    command_info.name
+                    "Failed setting info for command {full_name}."
                 )));
             }
 
@@ -462,6 +832,10 @@ api! {[
                 }
             });
 
+            if let Some(acl_categories) = command_info.acl_categories.as_deref() {
+                set_command_acl_categories(command, &full_name, acl_categories)?;
+            }
+
             Ok(())
         })
     }
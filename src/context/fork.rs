@@ -0,0 +1,139 @@
+use std::os::raw::{c_int, c_void};
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+use crate::{raw, Context, RedisError, RedisResult, Status, MODULE_CONTEXT};
+
+type ForkDoneCallback = Box<dyn FnOnce(&Context, i32, bool) + Send>;
+
+/// The done callback of the running fork child, along with its pid. Redis
+/// runs a single fork child at a time, so there is at most one callback.
+static FORK_DONE_CALLBACK: Mutex<Option<(i32, ForkDoneCallback)>> = Mutex::new(None);
+
+extern "C" fn fork_done_callback(exitcode: c_int, bysignal: c_int, _user_data: *mut c_void) {
+    let callback = FORK_DONE_CALLBACK.lock().unwrap().take();
+    if let Some((_, callback)) = callback {
+        let ctx = Context::new(MODULE_CONTEXT.ctx.load(Ordering::Relaxed));
+        callback(&ctx, exitcode, bysignal != 0);
+    }
+}
+
+/// The result of [`Context::fork`], telling on which side of the fork the
+/// code is running.
+pub enum ForkResult {
+    /// Running in the parent process, the handle refers to the child.
+    Parent(ForkHandle),
+    /// Running in the child process.
+    Child(ChildContext),
+}
+
+/// A handle to the fork child, held by the parent process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForkHandle {
+    pid: i32,
+}
+
+impl ForkHandle {
+    #[must_use]
+    pub const fn pid(&self) -> i32 {
+        self.pid
+    }
+
+    /// Kills the fork child. The done callback given to [`Context::fork`]
+    /// is not called in that case.
+    ///
+    /// Returns an error if the child is not running anymore.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_KillForkChild` is missing in redismodule.h
+    pub fn kill(self) -> RedisResult<()> {
+        let status: Status = unsafe { raw::RedisModule_KillForkChild.unwrap()(self.pid) }.into();
+        if status == Status::Err {
+            return Err(RedisError::Str("Fork child is not running"));
+        }
+        let mut callback = FORK_DONE_CALLBACK.lock().unwrap();
+        if matches!(*callback, Some((pid, _)) if pid == self.pid) {
+            callback.take();
+        }
+        Ok(())
+    }
+}
+
+/// The context of the fork child.
+///
+/// The child must finish with [`ChildContext::exit`]. If the context is
+/// dropped instead, the child exits with a failure exit code.
+pub struct ChildContext {
+    _private: (),
+}
+
+impl ChildContext {
+    /// Reports the progress of the child, between `0.0` and `1.0`, to the
+    /// parent process. The progress is shown in `INFO` as
+    /// `current_fork_perc`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_SendChildHeartbeat` is missing in redismodule.h
+    pub fn send_heartbeat(&self, progress: f64) {
+        unsafe { raw::RedisModule_SendChildHeartbeat.unwrap()(progress.clamp(0.0, 1.0)) };
+    }
+
+    /// Exits the child process with `exit_code`, which is passed to the done
+    /// callback in the parent process.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ExitFromChild` is missing in redismodule.h
+    pub fn exit(self, exit_code: i32) -> ! {
+        let ctx = std::mem::ManuallyDrop::new(self);
+        ctx.exit_from_child(exit_code)
+    }
+
+    fn exit_from_child(&self, exit_code: i32) -> ! {
+        unsafe { raw::RedisModule_ExitFromChild.unwrap()(exit_code) };
+        unreachable!("RedisModule_ExitFromChild returned")
+    }
+}
+
+impl Drop for ChildContext {
+    fn drop(&mut self) {
+        self.exit_from_child(1);
+    }
+}
+
+impl Context {
+    /// Forks the Redis process, like Redis does to save the RDB file in the
+    /// background. The child gets a copy-on-write snapshot of the dataset,
+    /// so it can serialize it without blocking the main thread.
+    ///
+    /// `on_done` is called in the parent, on the main thread, once the child
+    /// exited, with the exit code of the child and whether it was killed by
+    /// a signal.
+    ///
+    /// Returns an error if a fork child is already running (including the
+    /// one of an RDB save or an AOF rewrite).
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_Fork` is missing in redismodule.h
+    pub fn fork<F: FnOnce(&Self, i32, bool) + Send + 'static>(
+        &self,
+        on_done: F,
+    ) -> RedisResult<ForkResult> {
+        let pid = unsafe {
+            raw::RedisModule_Fork.unwrap()(Some(fork_done_callback), std::ptr::null_mut())
+        };
+        match pid {
+            -1 => Err(RedisError::Str("Failed forking the process")),
+            0 => Ok(ForkResult::Child(ChildContext { _private: () })),
+            pid => {
+                // The done callback runs on the main thread, so it cannot
+                // run before the callback is set.
+                *FORK_DONE_CALLBACK.lock().unwrap() = Some((pid, Box::new(on_done)));
+                Ok(ForkResult::Parent(ForkHandle { pid }))
+            }
+        }
+    }
+}
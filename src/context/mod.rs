@@ -27,9 +27,16 @@ use self::thread_safe::RedisLockIndicator;
 
 mod timer;
 
+pub mod acl;
+pub mod auth;
+pub mod aux_data;
 pub mod blocked;
 pub mod call_reply;
+pub mod client;
+pub mod cluster;
+pub mod command_filter;
 pub mod commands;
+pub mod fork;
 pub mod info;
 pub mod keys_cursor;
 pub mod server_events;
@@ -391,6 +398,41 @@ impl Context {
         }
     }
 
+    /// Returns `true` if the command was called to get the positions of
+    /// its channels, for a command declared with the `getchannels-api`
+    /// flag. The command must then report its channels with
+    /// [`Context::channel_at_pos`] instead of running.
+    ///
+    /// Always returns `false` on Redis versions which do not support it.
+    #[must_use]
+    pub fn is_channels_position_request(&self) -> bool {
+        // We want this to be available in tests where we don't have an actual Redis to call
+        if cfg!(test) {
+            return false;
+        }
+
+        let Some(is_channels_position_request) =
+            (unsafe { raw::RedisModule_IsChannelsPositionRequest })
+        else {
+            return false;
+        };
+        (unsafe { is_channels_position_request(self.ctx) }) != 0
+    }
+
+    /// Reports the argument at `pos` as a channel of the command, with
+    /// the way the command uses it, when [`Context::is_channels_position_request`]
+    /// returns `true`. The channels are checked against the ACL channel
+    /// permissions of the user.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ChannelAtPosWithFlags` is missing in redismodule.h
+    pub fn channel_at_pos(&self, pos: i32, flags: &AclChannelPermissions) {
+        unsafe {
+            raw::RedisModule_ChannelAtPosWithFlags.unwrap()(self.ctx, pos as c_int, flags.bits());
+        }
+    }
+
     fn call_internal<
         'ctx,
         'a,
@@ -662,6 +704,35 @@ impl Context {
         unsafe { raw::notify_keyspace_event(self.ctx, event_type, event, keyname) }
     }
 
+    /// Publishes the message to the clients subscribed to the channel, as
+    /// `PUBLISH` does, and returns the number of clients of this node which
+    /// received it.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_PublishMessage` is missing in redismodule.h
+    #[allow(clippy::must_use_candidate)]
+    pub fn publish(&self, channel: &RedisString, message: &RedisString) -> usize {
+        let receivers = unsafe {
+            raw::RedisModule_PublishMessage.unwrap()(self.ctx, channel.inner, message.inner)
+        };
+        receivers as usize
+    }
+
+    api!(
+        [RedisModule_PublishMessageShard],
+        /// Publishes the message to the clients subscribed to the shard
+        /// channel, as `SPUBLISH` does, and returns the number of clients of
+        /// this node which received it. In cluster mode, the command
+        /// publishing the message should declare the channel as a key with
+        /// the `NOT_KEY` flag, so it is routed to the shard owning it.
+        pub fn publish_shard(&self, channel: &RedisString, message: &RedisString) -> usize {
+            let receivers =
+                unsafe { RedisModule_PublishMessageShard(self.ctx, channel.inner, message.inner) };
+            receivers as usize
+        }
+    );
+
     pub fn current_command_name(&self) -> Result<String, RedisError> {
         unsafe {
             match raw::RedisModule_GetCurrentCommandName {
@@ -784,6 +855,60 @@ impl Context {
         acl_permission_result.map_err(|_e| RedisError::Str("User does not have permissions on key"))
     }
 
+    /// Verify that the given user can run the given command, with its
+    /// arguments. Return Ok(()) if the user has the permissions or error (with
+    /// relevant error message) if the validation failed.
+    pub fn acl_check_command_permission(
+        &self,
+        user_name: &RedisString,
+        args: &[&RedisString],
+    ) -> Result<(), RedisError> {
+        let user = unsafe { raw::RedisModule_GetModuleUserFromUserName.unwrap()(user_name.inner) };
+        if user.is_null() {
+            return Err(RedisError::Str("User does not exists or disabled"));
+        }
+        let mut args: Vec<_> = args.iter().map(|arg| arg.inner).collect();
+        let acl_permission_result: raw::Status = unsafe {
+            raw::RedisModule_ACLCheckCommandPermissions.unwrap()(
+                user,
+                args.as_mut_ptr(),
+                args.len() as c_int,
+            )
+        }
+        .into();
+        unsafe { raw::RedisModule_FreeModuleUser.unwrap()(user) };
+        let acl_permission_result: Result<(), &str> = acl_permission_result.into();
+        acl_permission_result
+            .map_err(|_e| RedisError::Str("User does not have permissions to run the command"))
+    }
+
+    /// Verify that the given user has the given ACL permission on the given
+    /// pubsub channel. Return Ok(()) if the user has the permissions or error
+    /// (with relevant error message) if the validation failed.
+    pub fn acl_check_channel_permission(
+        &self,
+        user_name: &RedisString,
+        channel: &RedisString,
+        permissions: &AclChannelPermissions,
+    ) -> Result<(), RedisError> {
+        let user = unsafe { raw::RedisModule_GetModuleUserFromUserName.unwrap()(user_name.inner) };
+        if user.is_null() {
+            return Err(RedisError::Str("User does not exists or disabled"));
+        }
+        let acl_permission_result: raw::Status = unsafe {
+            raw::RedisModule_ACLCheckChannelPermissions.unwrap()(
+                user,
+                channel.inner,
+                permissions.bits(),
+            )
+        }
+        .into();
+        unsafe { raw::RedisModule_FreeModuleUser.unwrap()(user) };
+        let acl_permission_result: Result<(), &str> = acl_permission_result.into();
+        acl_permission_result
+            .map_err(|_e| RedisError::Str("User does not have permissions on channel"))
+    }
+
     api!(
         [RedisModule_AddPostNotificationJob],
         /// When running inside a key space notification callback, it is dangerous and highly discouraged to perform any write
@@ -882,6 +1007,27 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// An object represent ACL permissions on pubsub channels.
+    /// Used to check ACL permission using `acl_check_channel_permission`,
+    /// and to report the channels of a command using `channel_at_pos`.
+    #[derive(Debug)]
+    pub struct AclChannelPermissions : c_int {
+        /// The channel is a pattern, checked literally against the patterns
+        /// of the user, as done by `PSUBSCRIBE`.
+        const PATTERN = raw::REDISMODULE_CMD_CHANNEL_PATTERN as c_int;
+
+        /// User can publish to the channel.
+        const PUBLISH = raw::REDISMODULE_CMD_CHANNEL_PUBLISH as c_int;
+
+        /// User can subscribe to the channel.
+        const SUBSCRIBE = raw::REDISMODULE_CMD_CHANNEL_SUBSCRIBE as c_int;
+
+        /// User can unsubscribe from the channel.
+        const UNSUBSCRIBE = raw::REDISMODULE_CMD_CHANNEL_UNSUBSCRIBE as c_int;
+    }
+}
+
 /// The values allowed in the "info" sections and dictionaries.
 #[derive(Debug, Clone)]
 pub enum InfoContextBuilderFieldBottomLevelValue {
@@ -1,7 +1,10 @@
 use std::ffi::CStr;
+use std::mem::ManuallyDrop;
 
-use crate::{context::Context, RedisError};
+use crate::key::RedisKey;
+use crate::{context::Context, RedisError, RedisString};
 use crate::{raw, InfoContext, RedisResult};
+use bitflags::bitflags;
 use linkme::distributed_slice;
 
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
@@ -31,12 +34,128 @@ pub enum ModuleChangeSubevent {
     Unloaded,
 }
 
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum ClientChangeSubevent {
+    Connected,
+    Disconnected,
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ClientInfoFlags: u64 {
+        const SSL = raw::REDISMODULE_CLIENTINFO_FLAG_SSL as u64;
+        const PUBSUB = raw::REDISMODULE_CLIENTINFO_FLAG_PUBSUB as u64;
+        const BLOCKED = raw::REDISMODULE_CLIENTINFO_FLAG_BLOCKED as u64;
+        const TRACKING = raw::REDISMODULE_CLIENTINFO_FLAG_TRACKING as u64;
+        const UNIXSOCKET = raw::REDISMODULE_CLIENTINFO_FLAG_UNIXSOCKET as u64;
+        const MULTI = raw::REDISMODULE_CLIENTINFO_FLAG_MULTI as u64;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub flags: ClientInfoFlags,
+    pub id: u64,
+    /// The IPv4 or IPv6 address of the client.
+    pub addr: String,
+    pub port: u16,
+    /// The selected database.
+    pub db: u16,
+}
+
+impl From<&raw::RedisModuleClientInfo> for ClientInfo {
+    fn from(info: &raw::RedisModuleClientInfo) -> Self {
+        let addr = unsafe { CStr::from_ptr(info.addr.as_ptr()) };
+        Self {
+            flags: ClientInfoFlags::from_bits_truncate(info.flags),
+            id: info.id,
+            addr: addr.to_string_lossy().into_owned(),
+            port: info.port,
+            db: info.db,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum ReplicaChangeSubevent {
+    Online,
+    Offline,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum PersistenceSubevent {
+    RdbStarted,
+    AofStarted,
+    SyncRdbStarted,
+    SyncAofStarted,
+    Ended,
+    Failed,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum MasterLinkChangeSubevent {
+    Up,
+    Down,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub struct SwapDbInfo {
+    pub first: i32,
+    pub second: i32,
+}
+
+/// Deprecated since Redis 7.0, see [`ReplAsyncLoadSubevent`].
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum ReplBackupSubevent {
+    Create,
+    Restore,
+    Discard,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum ReplAsyncLoadSubevent {
+    Started,
+    Aborted,
+    Completed,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum ForkChildSubevent {
+    Born,
+    Died,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum EventLoopSubevent {
+    BeforeSleep,
+    AfterSleep,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum KeySubevent {
+    Deleted,
+    Expired,
+    Evicted,
+    Overwritten,
+}
+
 #[derive(Clone)]
 pub enum ServerEventHandler {
     RuleChanged(fn(&Context, ServerRole)),
     Loading(fn(&Context, LoadingSubevent)),
     Flush(fn(&Context, FlushSubevent)),
     ModuleChange(fn(&Context, ModuleChangeSubevent)),
+    ClientChanged(fn(&Context, ClientChangeSubevent, &ClientInfo)),
+    Shutdown(fn(&Context)),
+    ReplicaChanged(fn(&Context, ReplicaChangeSubevent)),
+    Persistence(fn(&Context, PersistenceSubevent)),
+    MasterLinkChanged(fn(&Context, MasterLinkChangeSubevent)),
+    SwapDb(fn(&Context, SwapDbInfo)),
+    ReplBackup(fn(&Context, ReplBackupSubevent)),
+    ReplAsyncLoad(fn(&Context, ReplAsyncLoadSubevent)),
+    ForkChild(fn(&Context, ForkChildSubevent)),
+    EventLoop(fn(&Context, EventLoopSubevent)),
+    Key(fn(&Context, KeySubevent, &RedisString, &RedisKey)),
 }
 
 #[distributed_slice()]
@@ -57,6 +176,40 @@ pub static CONFIG_CHANGED_SERVER_EVENTS_LIST: [fn(&Context, &[&str])] = [..];
 #[distributed_slice()]
 pub static CRON_SERVER_EVENTS_LIST: [fn(&Context, u64)] = [..];
 
+#[distributed_slice()]
+pub static CLIENT_CHANGED_SERVER_EVENTS_LIST: [fn(&Context, ClientChangeSubevent, &ClientInfo)] =
+    [..];
+
+#[distributed_slice()]
+pub static SHUTDOWN_SERVER_EVENTS_LIST: [fn(&Context)] = [..];
+
+#[distributed_slice()]
+pub static REPLICA_CHANGED_SERVER_EVENTS_LIST: [fn(&Context, ReplicaChangeSubevent)] = [..];
+
+#[distributed_slice()]
+pub static PERSISTENCE_SERVER_EVENTS_LIST: [fn(&Context, PersistenceSubevent)] = [..];
+
+#[distributed_slice()]
+pub static MASTER_LINK_CHANGED_SERVER_EVENTS_LIST: [fn(&Context, MasterLinkChangeSubevent)] = [..];
+
+#[distributed_slice()]
+pub static SWAPDB_SERVER_EVENTS_LIST: [fn(&Context, SwapDbInfo)] = [..];
+
+#[distributed_slice()]
+pub static REPL_BACKUP_SERVER_EVENTS_LIST: [fn(&Context, ReplBackupSubevent)] = [..];
+
+#[distributed_slice()]
+pub static REPL_ASYNC_LOAD_SERVER_EVENTS_LIST: [fn(&Context, ReplAsyncLoadSubevent)] = [..];
+
+#[distributed_slice()]
+pub static FORK_CHILD_SERVER_EVENTS_LIST: [fn(&Context, ForkChildSubevent)] = [..];
+
+#[distributed_slice()]
+pub static EVENTLOOP_SERVER_EVENTS_LIST: [fn(&Context, EventLoopSubevent)] = [..];
+
+#[distributed_slice()]
+pub static KEY_SERVER_EVENTS_LIST: [fn(&Context, KeySubevent, &RedisString, &RedisKey)] = [..];
+
 #[distributed_slice()]
 pub static INFO_COMMAND_HANDLER_LIST: [fn(&InfoContext, bool) -> RedisResult<()>] = [..];
 
@@ -175,13 +328,248 @@ extern "C" fn config_change_event_callback(
         });
 }
 
+extern "C" fn client_change_event_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _eid: raw::RedisModuleEvent,
+    subevent: u64,
+    data: *mut ::std::os::raw::c_void,
+) {
+    let client_change_sub_event = match subevent {
+        raw::REDISMODULE_SUBEVENT_CLIENT_CHANGE_CONNECTED => ClientChangeSubevent::Connected,
+        raw::REDISMODULE_SUBEVENT_CLIENT_CHANGE_DISCONNECTED => ClientChangeSubevent::Disconnected,
+        _ => return,
+    };
+    let data: &raw::RedisModuleClientInfo = unsafe { &*(data as *mut raw::RedisModuleClientInfo) };
+    let client_info = ClientInfo::from(data);
+    let ctx = Context::new(ctx);
+    CLIENT_CHANGED_SERVER_EVENTS_LIST
+        .iter()
+        .for_each(|callback| {
+            callback(&ctx, client_change_sub_event, &client_info);
+        });
+}
+
+extern "C" fn shutdown_event_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _eid: raw::RedisModuleEvent,
+    _subevent: u64,
+    _data: *mut ::std::os::raw::c_void,
+) {
+    let ctx = Context::new(ctx);
+    SHUTDOWN_SERVER_EVENTS_LIST.iter().for_each(|callback| {
+        callback(&ctx);
+    });
+}
+
+extern "C" fn replica_change_event_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _eid: raw::RedisModuleEvent,
+    subevent: u64,
+    _data: *mut ::std::os::raw::c_void,
+) {
+    let replica_change_sub_event = match subevent {
+        raw::REDISMODULE_SUBEVENT_REPLICA_CHANGE_ONLINE => ReplicaChangeSubevent::Online,
+        raw::REDISMODULE_SUBEVENT_REPLICA_CHANGE_OFFLINE => ReplicaChangeSubevent::Offline,
+        _ => return,
+    };
+    let ctx = Context::new(ctx);
+    REPLICA_CHANGED_SERVER_EVENTS_LIST
+        .iter()
+        .for_each(|callback| {
+            callback(&ctx, replica_change_sub_event);
+        });
+}
+
+extern "C" fn persistence_event_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _eid: raw::RedisModuleEvent,
+    subevent: u64,
+    _data: *mut ::std::os::raw::c_void,
+) {
+    let persistence_sub_event = match subevent {
+        raw::REDISMODULE_SUBEVENT_PERSISTENCE_RDB_START => PersistenceSubevent::RdbStarted,
+        raw::REDISMODULE_SUBEVENT_PERSISTENCE_AOF_START => PersistenceSubevent::AofStarted,
+        raw::REDISMODULE_SUBEVENT_PERSISTENCE_SYNC_RDB_START => PersistenceSubevent::SyncRdbStarted,
+        raw::REDISMODULE_SUBEVENT_PERSISTENCE_SYNC_AOF_START => PersistenceSubevent::SyncAofStarted,
+        raw::REDISMODULE_SUBEVENT_PERSISTENCE_ENDED => PersistenceSubevent::Ended,
+        raw::REDISMODULE_SUBEVENT_PERSISTENCE_FAILED => PersistenceSubevent::Failed,
+        _ => return,
+    };
+    let ctx = Context::new(ctx);
+    PERSISTENCE_SERVER_EVENTS_LIST.iter().for_each(|callback| {
+        callback(&ctx, persistence_sub_event);
+    });
+}
+
+extern "C" fn master_link_change_event_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _eid: raw::RedisModuleEvent,
+    subevent: u64,
+    _data: *mut ::std::os::raw::c_void,
+) {
+    let master_link_sub_event = match subevent {
+        raw::REDISMODULE_SUBEVENT_MASTER_LINK_UP => MasterLinkChangeSubevent::Up,
+        raw::REDISMODULE_SUBEVENT_MASTER_LINK_DOWN => MasterLinkChangeSubevent::Down,
+        _ => return,
+    };
+    let ctx = Context::new(ctx);
+    MASTER_LINK_CHANGED_SERVER_EVENTS_LIST
+        .iter()
+        .for_each(|callback| {
+            callback(&ctx, master_link_sub_event);
+        });
+}
+
+extern "C" fn swapdb_event_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _eid: raw::RedisModuleEvent,
+    _subevent: u64,
+    data: *mut ::std::os::raw::c_void,
+) {
+    let data: &raw::RedisModuleSwapDbInfo = unsafe { &*(data as *mut raw::RedisModuleSwapDbInfo) };
+    let swapdb_info = SwapDbInfo {
+        first: data.dbnum_first,
+        second: data.dbnum_second,
+    };
+    let ctx = Context::new(ctx);
+    SWAPDB_SERVER_EVENTS_LIST.iter().for_each(|callback| {
+        callback(&ctx, swapdb_info);
+    });
+}
+
+extern "C" fn repl_backup_event_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _eid: raw::RedisModuleEvent,
+    subevent: u64,
+    _data: *mut ::std::os::raw::c_void,
+) {
+    let repl_backup_sub_event = match subevent {
+        raw::REDISMODULE_SUBEVENT_REPL_BACKUP_CREATE => ReplBackupSubevent::Create,
+        raw::REDISMODULE_SUBEVENT_REPL_BACKUP_RESTORE => ReplBackupSubevent::Restore,
+        raw::REDISMODULE_SUBEVENT_REPL_BACKUP_DISCARD => ReplBackupSubevent::Discard,
+        _ => return,
+    };
+    let ctx = Context::new(ctx);
+    REPL_BACKUP_SERVER_EVENTS_LIST.iter().for_each(|callback| {
+        callback(&ctx, repl_backup_sub_event);
+    });
+}
+
+extern "C" fn repl_async_load_event_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _eid: raw::RedisModuleEvent,
+    subevent: u64,
+    _data: *mut ::std::os::raw::c_void,
+) {
+    let repl_async_load_sub_event = match subevent {
+        raw::REDISMODULE_SUBEVENT_REPL_ASYNC_LOAD_STARTED => ReplAsyncLoadSubevent::Started,
+        raw::REDISMODULE_SUBEVENT_REPL_ASYNC_LOAD_ABORTED => ReplAsyncLoadSubevent::Aborted,
+        raw::REDISMODULE_SUBEVENT_REPL_ASYNC_LOAD_COMPLETED => ReplAsyncLoadSubevent::Completed,
+        _ => return,
+    };
+    let ctx = Context::new(ctx);
+    REPL_ASYNC_LOAD_SERVER_EVENTS_LIST
+        .iter()
+        .for_each(|callback| {
+            callback(&ctx, repl_async_load_sub_event);
+        });
+}
+
+extern "C" fn fork_child_event_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _eid: raw::RedisModuleEvent,
+    subevent: u64,
+    _data: *mut ::std::os::raw::c_void,
+) {
+    let fork_child_sub_event = match subevent {
+        raw::REDISMODULE_SUBEVENT_FORK_CHILD_BORN => ForkChildSubevent::Born,
+        raw::REDISMODULE_SUBEVENT_FORK_CHILD_DIED => ForkChildSubevent::Died,
+        _ => return,
+    };
+    let ctx = Context::new(ctx);
+    FORK_CHILD_SERVER_EVENTS_LIST.iter().for_each(|callback| {
+        callback(&ctx, fork_child_sub_event);
+    });
+}
+
+extern "C" fn eventloop_event_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _eid: raw::RedisModuleEvent,
+    subevent: u64,
+    _data: *mut ::std::os::raw::c_void,
+) {
+    let eventloop_sub_event = match subevent {
+        raw::REDISMODULE_SUBEVENT_EVENTLOOP_BEFORE_SLEEP => EventLoopSubevent::BeforeSleep,
+        raw::REDISMODULE_SUBEVENT_EVENTLOOP_AFTER_SLEEP => EventLoopSubevent::AfterSleep,
+        _ => return,
+    };
+    let ctx = Context::new(ctx);
+    EVENTLOOP_SERVER_EVENTS_LIST.iter().for_each(|callback| {
+        callback(&ctx, eventloop_sub_event);
+    });
+}
+
+extern "C" fn key_event_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    _eid: raw::RedisModuleEvent,
+    subevent: u64,
+    data: *mut ::std::os::raw::c_void,
+) {
+    let key_sub_event = match subevent {
+        raw::REDISMODULE_SUBEVENT_KEY_DELETED => KeySubevent::Deleted,
+        raw::REDISMODULE_SUBEVENT_KEY_EXPIRED => KeySubevent::Expired,
+        raw::REDISMODULE_SUBEVENT_KEY_EVICTED => KeySubevent::Evicted,
+        raw::REDISMODULE_SUBEVENT_KEY_OVERWRITTEN => KeySubevent::Overwritten,
+        _ => return,
+    };
+    let data: &raw::RedisModuleKeyInfo = unsafe { &*(data as *mut raw::RedisModuleKeyInfo) };
+    // The key is opened and closed by Redis, it must not be closed on drop.
+    let key = ManuallyDrop::new(RedisKey::from_raw_parts(ctx, data.key));
+    let key_name = unsafe { raw::RedisModule_GetKeyNameFromModuleKey.unwrap()(data.key) };
+    let key_name = RedisString::new(None, key_name.cast_mut());
+    let ctx = Context::new(ctx);
+    KEY_SERVER_EVENTS_LIST.iter().for_each(|callback| {
+        callback(&ctx, key_sub_event, &key_name, &key);
+    });
+}
+
+/// Checks whether the server knows about `server_event`. Only meaningful for
+/// events which have subevents.
+fn is_server_event_supported(server_event: u64) -> bool {
+    // `RedisModule_IsSubEventSupported` was only added in Redis 6.0.9, older
+    // servers are assumed to support the event.
+    let Some(is_sub_event_supported) = (unsafe { raw::RedisModule_IsSubEventSupported }) else {
+        return true;
+    };
+    let event = raw::RedisModuleEvent {
+        id: server_event,
+        dataver: 1,
+    };
+    unsafe { is_sub_event_supported(event, 0) != 0 }
+}
+
 fn register_single_server_event_type<T>(
     ctx: &Context,
-    callbacks: &[fn(&Context, T)],
+    callbacks: &[T],
+    server_event: u64,
+    inner_callback: raw::RedisModuleEventCallback,
+) -> Result<(), RedisError> {
+    register_server_event_type(ctx, callbacks, server_event, inner_callback, false)
+}
+
+fn register_server_event_type<T>(
+    ctx: &Context,
+    callbacks: &[T],
     server_event: u64,
     inner_callback: raw::RedisModuleEventCallback,
+    check_supported: bool,
 ) -> Result<(), RedisError> {
     if !callbacks.is_empty() {
+        if check_supported && !is_server_event_supported(server_event) {
+            return Err(RedisError::String(format!(
+                "Server event {server_event} is not supported by this Redis version"
+            )));
+        }
         let res = unsafe {
             raw::RedisModule_SubscribeToServerEvent.unwrap()(
                 ctx.ctx,
@@ -237,5 +625,81 @@ pub fn register_server_events(ctx: &Context) -> Result<(), RedisError> {
         raw::REDISMODULE_EVENT_CRON_LOOP,
         Some(cron_callback),
     )?;
+    register_server_event_type(
+        ctx,
+        &CLIENT_CHANGED_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_CLIENT_CHANGE,
+        Some(client_change_event_callback),
+        true,
+    )?;
+    // Shutdown and SwapDB have no subevents to check the support for.
+    register_single_server_event_type(
+        ctx,
+        &SHUTDOWN_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_SHUTDOWN,
+        Some(shutdown_event_callback),
+    )?;
+    register_server_event_type(
+        ctx,
+        &REPLICA_CHANGED_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_REPLICA_CHANGE,
+        Some(replica_change_event_callback),
+        true,
+    )?;
+    register_server_event_type(
+        ctx,
+        &PERSISTENCE_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_PERSISTENCE,
+        Some(persistence_event_callback),
+        true,
+    )?;
+    register_server_event_type(
+        ctx,
+        &MASTER_LINK_CHANGED_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_MASTER_LINK_CHANGE,
+        Some(master_link_change_event_callback),
+        true,
+    )?;
+    register_single_server_event_type(
+        ctx,
+        &SWAPDB_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_SWAPDB,
+        Some(swapdb_event_callback),
+    )?;
+    register_server_event_type(
+        ctx,
+        &REPL_BACKUP_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_REPL_BACKUP,
+        Some(repl_backup_event_callback),
+        true,
+    )?;
+    register_server_event_type(
+        ctx,
+        &REPL_ASYNC_LOAD_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_REPL_ASYNC_LOAD,
+        Some(repl_async_load_event_callback),
+        true,
+    )?;
+    register_server_event_type(
+        ctx,
+        &FORK_CHILD_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_FORK_CHILD,
+        Some(fork_child_event_callback),
+        true,
+    )?;
+    register_server_event_type(
+        ctx,
+        &EVENTLOOP_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_EVENTLOOP,
+        Some(eventloop_event_callback),
+        true,
+    )?;
+    register_server_event_type(
+        ctx,
+        &KEY_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_KEY,
+        Some(key_event_callback),
+        true,
+    )?;
     Ok(())
 }
@@ -74,6 +74,12 @@ impl<T> RedisGILGuard<T> {
             mutex: self,
         }
     }
+
+    /// Returns the guarded object, for callbacks which Redis calls with the
+    /// GIL held but without a context to lock it with.
+    pub(crate) fn get(&self) -> *mut T {
+        self.obj.get()
+    }
 }
 
 impl<T: Default> Default for RedisGILGuard<T> {
@@ -145,9 +151,13 @@ impl Default for ThreadSafeContext<DetachedFromClient> {
     }
 }
 
-impl ThreadSafeContext<BlockedClient> {
+impl<T: Send> ThreadSafeContext<BlockedClient<T>> {
+    /// The reply is sent through the returned context, the reply callback of
+    /// a client blocked with [`Context::block_client_with_reply`] is never
+    /// called.
     #[must_use]
-    pub fn with_blocked_client(blocked_client: BlockedClient) -> Self {
+    pub fn with_blocked_client(mut blocked_client: BlockedClient<T>) -> Self {
+        blocked_client.reply = None;
         let ctx = unsafe { raw::RedisModule_GetThreadSafeContext.unwrap()(blocked_client.inner) };
         Self {
             ctx,
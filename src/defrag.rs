@@ -0,0 +1,237 @@
+use std::mem::ManuallyDrop;
+use std::os::raw::{c_int, c_ulong, c_void};
+use std::sync::Mutex;
+
+use redis_module_macros_internals::api;
+
+use crate::{raw, Context, RedisError, RedisResult, RedisString, Status};
+
+/// Whether a defrag callback is done with the value, see [`Defrag::defrag`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefragStatus {
+    /// The value is fully defragmented.
+    Done,
+    /// The callback stopped early, it will be called again later and can
+    /// resume from the cursor set with [`DefragContext::set_cursor`].
+    Incomplete,
+}
+
+/// The context passed to the defrag callbacks, during the active
+/// defragmentation of Redis (`activedefrag`).
+pub struct DefragContext {
+    ctx: *mut raw::RedisModuleDefragCtx,
+}
+
+impl DefragContext {
+    #[must_use]
+    pub const fn new(ctx: *mut raw::RedisModuleDefragCtx) -> Self {
+        Self { ctx }
+    }
+
+    /// Returns true if the callback has used up its time budget and should
+    /// stop, to resume later from the current cursor.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DefragShouldStop` is missing in redismodule.h
+    #[must_use]
+    pub fn should_stop(&self) -> bool {
+        unsafe { raw::RedisModule_DefragShouldStop.unwrap()(self.ctx) != 0 }
+    }
+
+    /// Returns the cursor stored by a previous call of the callback on the
+    /// same value, `Some(0)` on the first call.
+    ///
+    /// Returns `None` if the callback cannot be resumed, in which case the
+    /// value has to be defragmented in one go.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DefragCursorGet` is missing in redismodule.h
+    #[must_use]
+    pub fn cursor(&self) -> Option<u64> {
+        let mut cursor: c_ulong = 0;
+        let status: Status =
+            unsafe { raw::RedisModule_DefragCursorGet.unwrap()(self.ctx, &mut cursor) }.into();
+        (status == Status::Ok).then_some(cursor as u64)
+    }
+
+    /// Stores the cursor to resume from when the callback is called again
+    /// after returning [`DefragStatus::Incomplete`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DefragCursorSet` is missing in redismodule.h
+    pub fn set_cursor(&self, cursor: u64) -> RedisResult<()> {
+        let status: Status =
+            unsafe { raw::RedisModule_DefragCursorSet.unwrap()(self.ctx, cursor as c_ulong) }
+                .into();
+        if status == Status::Err {
+            return Err(RedisError::Str("Defrag callback cannot be resumed"));
+        }
+        Ok(())
+    }
+
+    /// Moves the allocation at `ptr` to a less fragmented memory area.
+    /// Returns the new pointer, or `None` if the allocation was not moved,
+    /// in which case `ptr` is still valid.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been allocated by the Redis allocator. When it is
+    /// moved, `ptr` is freed and must not be used anymore.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DefragAlloc` is missing in redismodule.h
+    pub unsafe fn defrag_alloc<T>(&self, ptr: *mut T) -> Option<*mut T> {
+        let new_ptr = raw::RedisModule_DefragAlloc.unwrap()(self.ctx, ptr.cast::<c_void>());
+        (!new_ptr.is_null()).then_some(new_ptr.cast::<T>())
+    }
+
+    /// Moves the heap allocation of the box, see [`Self::defrag_alloc`].
+    ///
+    /// # Safety
+    ///
+    /// The box must have been allocated by the Redis allocator, that is the
+    /// module must use [`crate::alloc::RedisAlloc`] as its global allocator.
+    #[must_use]
+    pub unsafe fn defrag_box<T>(&self, value: Box<T>) -> Box<T> {
+        if std::mem::size_of::<T>() == 0 {
+            return value;
+        }
+        let ptr = Box::into_raw(value);
+        Box::from_raw(self.defrag_alloc(ptr).unwrap_or(ptr))
+    }
+
+    /// Moves the buffer of the vector, see [`Self::defrag_alloc`]. The
+    /// elements themselves are not defragmented.
+    ///
+    /// # Safety
+    ///
+    /// The vector must have been allocated by the Redis allocator, that is
+    /// the module must use [`crate::alloc::RedisAlloc`] as its global
+    /// allocator.
+    #[must_use]
+    pub unsafe fn defrag_vec<T>(&self, value: Vec<T>) -> Vec<T> {
+        if value.capacity() == 0 || std::mem::size_of::<T>() == 0 {
+            return value;
+        }
+        let mut value = ManuallyDrop::new(value);
+        let ptr = value.as_mut_ptr();
+        let ptr = self.defrag_alloc(ptr).unwrap_or(ptr);
+        Vec::from_raw_parts(ptr, value.len(), value.capacity())
+    }
+
+    /// Moves the given string, if it is not shared with other owners.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DefragRedisModuleString` is missing in redismodule.h
+    pub fn defrag_redis_string(&self, value: &mut RedisString) {
+        let inner =
+            unsafe { raw::RedisModule_DefragRedisModuleString.unwrap()(self.ctx, value.inner) };
+        if !inner.is_null() {
+            value.inner = inner;
+        }
+    }
+
+    api!(
+        [RedisModule_GetKeyNameFromDefragCtx],
+        /// Returns the name of the key being defragmented, or `None` when
+        /// called from a global defrag function.
+        pub fn key_name(&self) -> Option<RedisString> {
+            let key_name = unsafe { RedisModule_GetKeyNameFromDefragCtx(self.ctx) };
+            (!key_name.is_null()).then(|| RedisString::new(None, key_name.cast_mut()))
+        }
+    );
+
+    api!(
+        [RedisModule_GetDbIdFromDefragCtx],
+        /// Returns the id of the database of the key being defragmented.
+        pub fn db_id(&self) -> i32 {
+            unsafe { RedisModule_GetDbIdFromDefragCtx(self.ctx) }
+        }
+    );
+}
+
+/// Implemented by the values of custom data types to take part in the
+/// active defragmentation of Redis.
+///
+/// Set [`defrag_callback`] as the `defrag` method of the data type to use
+/// it. The value itself is moved by the callback, the implementation only
+/// needs to take care of the allocations it owns. Data types implemented
+/// with `#[redis_data_type]` implement
+/// [`crate::native_types::RedisDataType::defrag`] instead.
+pub trait Defrag {
+    /// Defragments the allocations owned by the value.
+    ///
+    /// Large values should check [`DefragContext::should_stop`] regularly,
+    /// store their progress with [`DefragContext::set_cursor`] and return
+    /// [`DefragStatus::Incomplete`] when asked to stop. The next call resumes
+    /// from [`DefragContext::cursor`].
+    fn defrag(&mut self, ctx: &DefragContext) -> DefragStatus;
+}
+
+/// The `defrag` method of a data type whose values are `Box<T>`.
+///
+/// # Safety
+///
+/// `value` must point to a `Box<T>` allocated by the Redis allocator, that
+/// is the module must use [`crate::alloc::RedisAlloc`] as its global
+/// allocator.
+pub unsafe extern "C" fn defrag_callback<T: Defrag>(
+    ctx: *mut raw::RedisModuleDefragCtx,
+    _key: *mut raw::RedisModuleString,
+    value: *mut *mut c_void,
+) -> c_int {
+    let ctx = DefragContext::new(ctx);
+    // Only move the value on the first call, resumed calls keep the value
+    // where the first call left it.
+    if matches!(ctx.cursor(), None | Some(0)) {
+        if let Some(new_value) = ctx.defrag_alloc(*value) {
+            *value = new_value;
+        }
+    }
+    let value = &mut *(*value).cast::<T>();
+    match value.defrag(&ctx) {
+        DefragStatus::Done => 0,
+        DefragStatus::Incomplete => 1,
+    }
+}
+
+/// The global defrag function of the module, see
+/// [`Context::register_defrag_function`].
+static DEFRAG_FUNCTION: Mutex<Option<fn(&DefragContext)>> = Mutex::new(None);
+
+extern "C" fn defrag_function_callback(ctx: *mut raw::RedisModuleDefragCtx) {
+    let defrag_function = *DEFRAG_FUNCTION.lock().unwrap();
+    if let Some(defrag_function) = defrag_function {
+        defrag_function(&DefragContext::new(ctx));
+    }
+}
+
+impl Context {
+    /// Registers `defrag_function` to defragment the global data of the
+    /// module, which is not stored in keys. It is called once per
+    /// defragmentation cycle and cannot be resumed, so it should keep its
+    /// work short.
+    ///
+    /// A module has a single defrag function, registering another one
+    /// replaces it.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_RegisterDefragFunc` is missing in redismodule.h
+    pub fn register_defrag_function(&self, defrag_function: fn(&DefragContext)) -> RedisResult<()> {
+        let status: Status = unsafe {
+            raw::RedisModule_RegisterDefragFunc.unwrap()(self.ctx, Some(defrag_function_callback))
+        }
+        .into();
+        if status == Status::Err {
+            return Err(RedisError::Str("Failed registering defrag function"));
+        }
+        *DEFRAG_FUNCTION.lock().unwrap() = Some(defrag_function);
+        Ok(())
+    }
+}
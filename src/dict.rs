@@ -0,0 +1,337 @@
+use crate::raw;
+use crate::RedisError;
+use crate::RedisString;
+use crate::Status;
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+/// The seek operators accepted by [`RedisDict::seek`] and
+/// [`DictIterator::reseek`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DictSeekOp {
+    /// `^`, the first (lexicographically smaller) key.
+    First,
+    /// `$`, the last (lexicographically bigger) key.
+    Last,
+    /// `>`, the first key greater than the given key.
+    Greater,
+    /// `>=`, the first key greater or equal than the given key.
+    GreaterOrEqual,
+    /// `<`, the first key smaller than the given key.
+    Less,
+    /// `<=`, the first key smaller or equal than the given key.
+    LessOrEqual,
+    /// `==`, the first key matching exactly the given key.
+    Equal,
+}
+
+impl TryFrom<&str> for DictSeekOp {
+    type Error = RedisError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "^" => Ok(Self::First),
+            "$" => Ok(Self::Last),
+            ">" => Ok(Self::Greater),
+            ">=" => Ok(Self::GreaterOrEqual),
+            "<" => Ok(Self::Less),
+            "<=" => Ok(Self::LessOrEqual),
+            "==" => Ok(Self::Equal),
+            _ => Err(RedisError::String(format!(
+                "Value {value} is not a valid dictionary seek operator."
+            ))),
+        }
+    }
+}
+
+impl DictSeekOp {
+    const fn as_ptr(self) -> *const c_char {
+        let op: &[u8] = match self {
+            Self::First => b"^\0",
+            Self::Last => b"$\0",
+            Self::Greater => b">\0",
+            Self::GreaterOrEqual => b">=\0",
+            Self::Less => b"<\0",
+            Self::LessOrEqual => b"<=\0",
+            Self::Equal => b"==\0",
+        };
+        op.as_ptr().cast::<c_char>()
+    }
+}
+
+/// Types that can be used as [`RedisDict`] keys: anything that can be viewed
+/// as bytes, and [`RedisString`].
+pub trait DictKey {
+    fn as_dict_key(&self) -> &[u8];
+}
+
+impl<T: AsRef<[u8]> + ?Sized> DictKey for T {
+    fn as_dict_key(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+impl DictKey for RedisString {
+    fn as_dict_key(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+/// An ordered map from binary keys to values of type `V`, backed by the Redis
+/// radix tree (`RedisModuleDict`).
+///
+/// The dictionary owns its values, they are dropped when they are removed or
+/// when the dictionary itself is dropped. Unlike a `BTreeMap`, the memory of
+/// the dictionary is allocated and accounted for by Redis.
+pub struct RedisDict<V> {
+    inner: *mut raw::RedisModuleDict,
+    phantom: PhantomData<V>,
+}
+
+impl<V> RedisDict<V> {
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_CreateDict` is missing in redismodule.h
+    #[must_use]
+    pub fn new() -> Self {
+        // A dictionary created without a context is not subject to automatic
+        // memory management, so it can outlive the current command.
+        let inner = unsafe { raw::RedisModule_CreateDict.unwrap()(ptr::null_mut()) };
+        Self {
+            inner,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the number of keys stored in the dictionary.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        unsafe { raw::RedisModule_DictSize.unwrap()(self.inner) as usize }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get_ptr<K: DictKey + ?Sized>(&self, key: &K) -> Option<*mut V> {
+        let key = key.as_dict_key();
+        let mut nokey: c_int = 0;
+        let value = unsafe {
+            raw::RedisModule_DictGetC.unwrap()(
+                self.inner,
+                key.as_ptr() as *mut c_void,
+                key.len(),
+                &mut nokey,
+            )
+        };
+        (nokey == 0).then_some(value.cast::<V>())
+    }
+
+    #[must_use]
+    pub fn get<K: DictKey + ?Sized>(&self, key: &K) -> Option<&V> {
+        self.get_ptr(key).map(|value| unsafe { &*value })
+    }
+
+    #[must_use]
+    pub fn get_mut<K: DictKey + ?Sized>(&mut self, key: &K) -> Option<&mut V> {
+        self.get_ptr(key).map(|value| unsafe { &mut *value })
+    }
+
+    #[must_use]
+    pub fn contains_key<K: DictKey + ?Sized>(&self, key: &K) -> bool {
+        self.get_ptr(key).is_some()
+    }
+
+    /// Inserts `value` under `key`, returning the value previously stored
+    /// under that key, if any.
+    pub fn insert<K: DictKey + ?Sized>(&mut self, key: &K, value: V) -> Option<V> {
+        let key = key.as_dict_key();
+        let old = self.get_ptr(key);
+        let value = Box::into_raw(Box::new(value)).cast::<c_void>();
+        unsafe {
+            raw::RedisModule_DictReplaceC.unwrap()(
+                self.inner,
+                key.as_ptr() as *mut c_void,
+                key.len(),
+                value,
+            )
+        };
+        old.map(|old| *unsafe { Box::from_raw(old) })
+    }
+
+    /// Removes `key` from the dictionary, returning its value if the key was
+    /// present.
+    pub fn remove<K: DictKey + ?Sized>(&mut self, key: &K) -> Option<V> {
+        let key = key.as_dict_key();
+        let mut old: *mut c_void = ptr::null_mut();
+        let res = unsafe {
+            raw::RedisModule_DictDelC.unwrap()(
+                self.inner,
+                key.as_ptr() as *mut c_void,
+                key.len(),
+                (&mut old as *mut *mut c_void).cast::<c_void>(),
+            )
+        };
+        if Status::Ok == res.into() {
+            Some(*unsafe { Box::from_raw(old.cast::<V>()) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the dictionary, in lexicographical order of
+    /// the keys.
+    #[must_use]
+    pub fn iter(&self) -> DictIterator<'_, V> {
+        self.seek(DictSeekOp::First, b"")
+    }
+
+    /// Returns an iterator positioned according to `op` and `key`, e.g.
+    /// `seek(DictSeekOp::GreaterOrEqual, "b")` starts at the first key that is
+    /// not smaller than `b`. The key is ignored by [`DictSeekOp::First`] and
+    /// [`DictSeekOp::Last`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DictIteratorStartC` is missing in redismodule.h
+    pub fn seek<K: DictKey + ?Sized>(&self, op: DictSeekOp, key: &K) -> DictIterator<'_, V> {
+        let key = key.as_dict_key();
+        let inner = unsafe {
+            raw::RedisModule_DictIteratorStartC.unwrap()(
+                self.inner,
+                op.as_ptr(),
+                key.as_ptr() as *mut c_void,
+                key.len(),
+            )
+        };
+        DictIterator {
+            inner,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<V> Default for RedisDict<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Drop for RedisDict<V> {
+    fn drop(&mut self) {
+        let mut iter = self.seek(DictSeekOp::First, b"");
+        while let Some((_, value)) = iter.next_ptr() {
+            drop(unsafe { Box::from_raw(value) });
+        }
+        drop(iter);
+        unsafe { raw::RedisModule_FreeDict.unwrap()(ptr::null_mut(), self.inner) };
+    }
+}
+
+/// An iterator over a [`RedisDict`], created with [`RedisDict::iter`] or
+/// [`RedisDict::seek`].
+///
+/// Besides the forward [`Iterator`] implementation, [`DictIterator::prev`]
+/// steps backward from the current position and [`DictIterator::reseek`]
+/// moves the iterator to another position.
+pub struct DictIterator<'dict, V> {
+    inner: *mut raw::RedisModuleDictIter,
+    phantom: PhantomData<&'dict V>,
+}
+
+impl<'dict, V> DictIterator<'dict, V> {
+    fn next_ptr(&mut self) -> Option<(Vec<u8>, *mut V)> {
+        let next = unsafe { raw::RedisModule_DictNextC };
+        self.step(next)
+    }
+
+    fn step(
+        &mut self,
+        step: Option<
+            unsafe extern "C" fn(
+                *mut raw::RedisModuleDictIter,
+                *mut usize,
+                *mut *mut c_void,
+            ) -> *mut c_void,
+        >,
+    ) -> Option<(Vec<u8>, *mut V)> {
+        let mut key_len = 0;
+        let mut value: *mut c_void = ptr::null_mut();
+        let key = unsafe { step.unwrap()(self.inner, &mut key_len, &mut value) };
+        if key.is_null() {
+            return None;
+        }
+        // The key is only valid until the next step, so it has to be copied.
+        let key = unsafe { std::slice::from_raw_parts(key.cast::<u8>(), key_len) }.to_vec();
+        Some((key, value.cast::<V>()))
+    }
+
+    /// Returns the element at the current position and moves the iterator
+    /// to the previous (lexicographically smaller) key.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DictPrevC` is missing in redismodule.h
+    pub fn prev(&mut self) -> Option<(Vec<u8>, &'dict V)> {
+        let prev = unsafe { raw::RedisModule_DictPrevC };
+        self.step(prev)
+            .map(|(key, value)| (key, unsafe { &*value }))
+    }
+
+    /// Moves the iterator to a new position, as [`RedisDict::seek`] does.
+    /// Returns `false` if no element matches the new position.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DictIteratorReseekC` is missing in redismodule.h
+    pub fn reseek<K: DictKey + ?Sized>(&mut self, op: DictSeekOp, key: &K) -> bool {
+        let key = key.as_dict_key();
+        let res = unsafe {
+            raw::RedisModule_DictIteratorReseekC.unwrap()(
+                self.inner,
+                op.as_ptr(),
+                key.as_ptr() as *mut c_void,
+                key.len(),
+            )
+        };
+        Status::Ok == res.into()
+    }
+
+    /// Compares the element at the current position with `key` using the
+    /// given operator, e.g. `compare(DictSeekOp::Less, "c")` returns `true`
+    /// when the current key is smaller than `c`. Returns `false` when the
+    /// iterator is exhausted.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DictCompareC` is missing in redismodule.h
+    #[must_use]
+    pub fn compare<K: DictKey + ?Sized>(&self, op: DictSeekOp, key: &K) -> bool {
+        let key = key.as_dict_key();
+        let res = unsafe {
+            raw::RedisModule_DictCompareC.unwrap()(
+                self.inner,
+                op.as_ptr(),
+                key.as_ptr() as *mut c_void,
+                key.len(),
+            )
+        };
+        Status::Ok == res.into()
+    }
+}
+
+impl<'dict, V> Iterator for DictIterator<'dict, V> {
+    type Item = (Vec<u8>, &'dict V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_ptr()
+            .map(|(key, value)| (key, unsafe { &*value }))
+    }
+}
+
+impl<'dict, V> Drop for DictIterator<'dict, V> {
+    fn drop(&mut self) {
+        unsafe { raw::RedisModule_DictIteratorStop.unwrap()(self.inner) };
+    }
+}
@@ -0,0 +1,101 @@
+use std::os::raw::c_char;
+
+use redis_module_macros_internals::api;
+
+use crate::{raw, RedisString};
+
+/// The digest of `DEBUG DIGEST` and `DEBUG DIGEST-VALUE`, given to
+/// [`crate::native_types::RedisDataType::digest`].
+///
+/// Elements are added to the current sequence, which is ended with
+/// [`Digest::end_sequence`]. The order of the elements within a sequence
+/// matters, the order of the sequences does not. Ordered collections, like
+/// lists, are added with [`Digest::add_ordered`] and unordered ones, like
+/// sets and hashes, with [`Digest::add_unordered`] and
+/// [`Digest::add_unordered_with`], so equal values have equal digests on
+/// the master and its replicas.
+pub struct Digest {
+    inner: *mut raw::RedisModuleDigest,
+}
+
+impl Digest {
+    #[must_use]
+    pub const fn new(inner: *mut raw::RedisModuleDigest) -> Self {
+        Self { inner }
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DigestAddStringBuffer` is missing in redismodule.h
+    pub fn add_string_buffer(&mut self, value: &[u8]) {
+        unsafe {
+            raw::RedisModule_DigestAddStringBuffer.unwrap()(
+                self.inner,
+                value.as_ptr().cast::<c_char>(),
+                value.len(),
+            )
+        };
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DigestAddLongLong` is missing in redismodule.h
+    pub fn add_long_long(&mut self, value: i64) {
+        unsafe { raw::RedisModule_DigestAddLongLong.unwrap()(self.inner, value) };
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_DigestEndSequence` is missing in redismodule.h
+    pub fn end_sequence(&mut self) {
+        unsafe { raw::RedisModule_DigestEndSequence.unwrap()(self.inner) };
+    }
+
+    /// Adds an ordered collection, such as a list, as a single sequence.
+    pub fn add_ordered<T: AsRef<[u8]>>(&mut self, items: impl IntoIterator<Item = T>) {
+        items
+            .into_iter()
+            .for_each(|item| self.add_string_buffer(item.as_ref()));
+        self.end_sequence();
+    }
+
+    /// Adds an unordered collection, such as a set, with a sequence for
+    /// each item.
+    pub fn add_unordered<T: AsRef<[u8]>>(&mut self, items: impl IntoIterator<Item = T>) {
+        self.add_unordered_with(items, |digest, item| {
+            digest.add_string_buffer(item.as_ref());
+        });
+    }
+
+    /// Adds an unordered collection, such as the fields and values of a
+    /// hash. `add` adds each item to its own sequence, which is ended after
+    /// it returns.
+    pub fn add_unordered_with<I: IntoIterator>(
+        &mut self,
+        items: I,
+        mut add: impl FnMut(&mut Self, I::Item),
+    ) {
+        items.into_iter().for_each(|item| {
+            add(self, item);
+            self.end_sequence();
+        });
+    }
+
+    api!(
+        [RedisModule_GetKeyNameFromDigest],
+        /// Returns the name of the key the digest is computed for.
+        pub fn key_name(&self) -> Option<RedisString> {
+            let key_name = unsafe { RedisModule_GetKeyNameFromDigest(self.inner) };
+            (!key_name.is_null()).then(|| RedisString::new(None, key_name.cast_mut()))
+        }
+    );
+
+    api!(
+        [RedisModule_GetDbIdFromDigest],
+        /// Returns the id of the database of the key the digest is computed
+        /// for.
+        pub fn db_id(&self) -> i32 {
+            unsafe { RedisModule_GetDbIdFromDigest(self.inner) }
+        }
+    );
+}
@@ -47,6 +47,18 @@ impl Display for Error {
     }
 }
 
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::generic(&msg.to_string())
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::generic(&msg.to_string())
+    }
+}
+
 impl error::Error for Error {
     fn cause(&self) -> Option<&dyn error::Error> {
         match *self {
@@ -10,13 +10,15 @@ use libc::size_t;
 
 use raw::KeyType;
 
-use crate::native_types::RedisType;
+use crate::native_types::{RedisDataType, RedisType};
 use crate::raw;
 use crate::redismodule::REDIS_OK;
 use crate::stream::StreamIterator;
+use crate::zset::{LexBound, ScoreBound, ZaddFlags, ZsetRangeIterator};
 use crate::RedisError;
 use crate::RedisResult;
 use crate::RedisString;
+use crate::Status;
 
 /// `RedisKey` is an abstraction over a Redis key that allows readonly
 /// operations.
@@ -75,6 +77,12 @@ impl RedisKey {
         Ok(Some(value))
     }
 
+    /// Returns the value of the key, checking it is of the data type of
+    /// `T`.
+    pub fn get_typed_value<T: RedisDataType>(&self) -> Result<Option<&T>, RedisError> {
+        self.get_value(T::redis_type())
+    }
+
     /// # Panics
     ///
     /// Will panic if `RedisModule_KeyType` is missing in redismodule.h
@@ -152,6 +160,35 @@ impl RedisKey {
     ) -> Result<StreamIterator, RedisError> {
         StreamIterator::new(self, from, to, exclusive, reverse)
     }
+
+    /// Returns the score of `element` in the sorted set stored at this key,
+    /// or `None` if the key or the element does not exist.
+    pub fn zset_score(&self, element: &RedisString) -> Result<Option<f64>, RedisError> {
+        zset_score_key(self.key_inner, element)
+    }
+
+    /// Iterates over the elements of the sorted set whose score is between
+    /// `min` and `max`, in ascending order (descending if `reverse` is set).
+    pub fn get_zset_score_range_iterator(
+        &self,
+        min: ScoreBound,
+        max: ScoreBound,
+        reverse: bool,
+    ) -> Result<ZsetRangeIterator<'_>, RedisError> {
+        ZsetRangeIterator::new_score_range(self.key_inner, min, max, reverse)
+    }
+
+    /// Iterates over the elements of the sorted set that are lexicographically
+    /// between `min` and `max`. All the elements are expected to have the same
+    /// score, as with `ZRANGEBYLEX`.
+    pub fn get_zset_lex_range_iterator(
+        &self,
+        min: LexBound,
+        max: LexBound,
+        reverse: bool,
+    ) -> Result<ZsetRangeIterator<'_>, RedisError> {
+        ZsetRangeIterator::new_lex_range(self.key_inner, min, max, reverse)
+    }
 }
 
 impl Drop for RedisKey {
@@ -354,6 +391,12 @@ impl RedisKeyWritable {
         Ok(Some(value))
     }
 
+    /// Returns the value of the key, checking it is of the data type of
+    /// `T`.
+    pub fn get_typed_value<T: RedisDataType>(&self) -> Result<Option<&mut T>, RedisError> {
+        self.get_value(T::redis_type())
+    }
+
     /// # Panics
     ///
     /// Will panic if `RedisModule_ModuleTypeSetValue` is missing in redismodule.h
@@ -372,6 +415,12 @@ impl RedisKeyWritable {
         status.into()
     }
 
+    /// Sets the value of the key, which must be empty or of the data type
+    /// of `T`.
+    pub fn set_typed_value<T: RedisDataType>(&self, value: T) -> Result<(), RedisError> {
+        self.set_value(T::redis_type(), value)
+    }
+
     pub fn trim_stream_by_id(
         &self,
         mut id: raw::RedisModuleStreamID,
@@ -391,6 +440,120 @@ impl RedisKeyWritable {
             Ok(res as usize)
         }
     }
+
+    /// Adds `element` with the given `score` to the sorted set stored at this
+    /// key, creating the sorted set if the key is empty.
+    ///
+    /// `flags` may contain [`ZaddFlags::XX`], [`ZaddFlags::NX`],
+    /// [`ZaddFlags::GT`] and [`ZaddFlags::LT`]. The returned flags tell
+    /// whether the element was added, updated or left untouched.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ZsetAdd` is missing in redismodule.h
+    pub fn zset_add(
+        &self,
+        score: f64,
+        element: &RedisString,
+        flags: ZaddFlags,
+    ) -> Result<ZaddFlags, RedisError> {
+        let mut flags = flags.bits();
+        let res = unsafe {
+            raw::RedisModule_ZsetAdd.unwrap()(self.key_inner, score, element.inner, &mut flags)
+        };
+        if Status::Ok == res.into() {
+            Ok(ZaddFlags::from_bits_truncate(flags))
+        } else {
+            Err(self.zset_error())
+        }
+    }
+
+    /// Increments the score of `element` by `score`, adding it to the sorted
+    /// set if needed, and returns the new score. `None` is returned when the
+    /// operation was skipped because of `flags`, see [`Self::zset_add`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ZsetIncrby` is missing in redismodule.h
+    pub fn zset_incrby(
+        &self,
+        score: f64,
+        element: &RedisString,
+        flags: ZaddFlags,
+    ) -> Result<Option<f64>, RedisError> {
+        let mut flags = flags.bits();
+        let mut new_score = 0.0;
+        let res = unsafe {
+            raw::RedisModule_ZsetIncrby.unwrap()(
+                self.key_inner,
+                score,
+                element.inner,
+                &mut flags,
+                &mut new_score,
+            )
+        };
+        if Status::Ok != res.into() {
+            return Err(self.zset_error());
+        }
+        if ZaddFlags::from_bits_truncate(flags).contains(ZaddFlags::NOP) {
+            Ok(None)
+        } else {
+            Ok(Some(new_score))
+        }
+    }
+
+    /// Removes `element` from the sorted set stored at this key. Returns
+    /// `true` if the element was found and removed.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ZsetRem` is missing in redismodule.h
+    pub fn zset_rem(&self, element: &RedisString) -> Result<bool, RedisError> {
+        let mut deleted = 0;
+        let res = unsafe {
+            raw::RedisModule_ZsetRem.unwrap()(self.key_inner, element.inner, &mut deleted)
+        };
+        if Status::Ok == res.into() {
+            Ok(deleted != 0)
+        } else {
+            Err(self.zset_error())
+        }
+    }
+
+    /// Returns the score of `element` in the sorted set stored at this key,
+    /// or `None` if the key or the element does not exist.
+    pub fn zset_score(&self, element: &RedisString) -> Result<Option<f64>, RedisError> {
+        zset_score_key(self.key_inner, element)
+    }
+
+    /// Same as [`RedisKey::get_zset_score_range_iterator`]. The key is
+    /// borrowed mutably so it can not be modified during the iteration.
+    pub fn get_zset_score_range_iterator(
+        &mut self,
+        min: ScoreBound,
+        max: ScoreBound,
+        reverse: bool,
+    ) -> Result<ZsetRangeIterator<'_>, RedisError> {
+        ZsetRangeIterator::new_score_range(self.key_inner, min, max, reverse)
+    }
+
+    /// Same as [`RedisKey::get_zset_lex_range_iterator`]. The key is
+    /// borrowed mutably so it can not be modified during the iteration.
+    pub fn get_zset_lex_range_iterator(
+        &mut self,
+        min: LexBound,
+        max: LexBound,
+        reverse: bool,
+    ) -> Result<ZsetRangeIterator<'_>, RedisError> {
+        ZsetRangeIterator::new_lex_range(self.key_inner, min, max, reverse)
+    }
+
+    fn zset_error(&self) -> RedisError {
+        match self.key_type() {
+            KeyType::Empty | KeyType::ZSet => RedisError::Str("Error while updating sorted set"),
+            _ => RedisError::WrongType,
+        }
+    }
 }
 
 /// Opaque type used to hold multi-get results. Use the provided methods to convert
@@ -600,6 +763,23 @@ where
     Ok(values)
 }
 
+fn zset_score_key(
+    key_inner: *mut raw::RedisModuleKey,
+    element: &RedisString,
+) -> Result<Option<f64>, RedisError> {
+    let key_type: KeyType = unsafe { raw::RedisModule_KeyType.unwrap()(key_inner) }.into();
+    match key_type {
+        KeyType::Empty => return Ok(None),
+        KeyType::ZSet => {}
+        _ => return Err(RedisError::WrongType),
+    }
+    let mut score = 0.0;
+    let res = unsafe { raw::RedisModule_ZsetScore.unwrap()(key_inner, element.inner, &mut score) };
+    // The key is known to be a sorted set, so an error means that the element
+    // is not a member of it.
+    Ok((Status::Ok == res.into()).then_some(score))
+}
+
 fn to_raw_mode(mode: KeyMode) -> raw::KeyMode {
     match mode {
         KeyMode::Read => raw::KeyMode::READ,
@@ -0,0 +1,88 @@
+use std::ptr;
+
+use crate::{raw, RedisString};
+
+/// Returns the key name returned by one of the `*FromOptCtx` functions.
+fn key_name(key_name: *const raw::RedisModuleString) -> Option<RedisString> {
+    (!key_name.is_null()).then(|| RedisString::new(None, key_name.cast_mut()))
+}
+
+/// The context passed to the callbacks of a custom data type which are
+/// called for a key, see for example
+/// [`crate::native_types::RedisDataType::copy`].
+///
+/// Redis versions before 7.0 have no such context, they only give the key
+/// names to some of the callbacks. The context then returns these names, and
+/// -1 for the databases.
+pub struct KeyOptCtx {
+    ctx: *mut raw::RedisModuleKeyOptCtx,
+    from_key: *mut raw::RedisModuleString,
+    to_key: *mut raw::RedisModuleString,
+}
+
+impl KeyOptCtx {
+    #[must_use]
+    pub const fn new(ctx: *mut raw::RedisModuleKeyOptCtx) -> Self {
+        Self {
+            ctx,
+            from_key: ptr::null_mut(),
+            to_key: ptr::null_mut(),
+        }
+    }
+
+    /// Creates the context of a callback of a Redis version before 7.0,
+    /// from the key names it was given.
+    #[must_use]
+    pub const fn from_keys(
+        from_key: *mut raw::RedisModuleString,
+        to_key: *mut raw::RedisModuleString,
+    ) -> Self {
+        Self {
+            ctx: ptr::null_mut(),
+            from_key,
+            to_key,
+        }
+    }
+
+    // Only Redis 7.0 and above create a context, so the functions below
+    // exist whenever there is one.
+
+    /// Returns the name of the key, the source key for `COPY`.
+    #[must_use]
+    pub fn key_name(&self) -> Option<RedisString> {
+        if self.ctx.is_null() {
+            return key_name(self.from_key);
+        }
+        key_name(unsafe { raw::RedisModule_GetKeyNameFromOptCtx.unwrap()(self.ctx) })
+    }
+
+    /// Returns the name of the destination key for `COPY`, `None`
+    /// otherwise.
+    #[must_use]
+    pub fn to_key_name(&self) -> Option<RedisString> {
+        if self.ctx.is_null() {
+            return key_name(self.to_key);
+        }
+        key_name(unsafe { raw::RedisModule_GetToKeyNameFromOptCtx.unwrap()(self.ctx) })
+    }
+
+    /// Returns the id of the database of the key, the source key for
+    /// `COPY`, or -1 if it is unknown.
+    #[must_use]
+    pub fn db_id(&self) -> i32 {
+        if self.ctx.is_null() {
+            return -1;
+        }
+        unsafe { raw::RedisModule_GetDbIdFromOptCtx.unwrap()(self.ctx) }
+    }
+
+    /// Returns the id of the database of the destination key for `COPY`,
+    /// -1 otherwise or if it is unknown.
+    #[must_use]
+    pub fn to_db_id(&self) -> i32 {
+        if self.ctx.is_null() {
+            return -1;
+        }
+        unsafe { raw::RedisModule_GetToDbIdFromOptCtx.unwrap()(self.ctx) }
+    }
+}
@@ -2,15 +2,23 @@ pub use crate::context::InfoContext;
 extern crate num_traits;
 
 pub mod alloc;
+pub mod aof;
 pub mod apierror;
+pub mod args;
+pub mod defrag;
+pub mod dict;
+pub mod digest;
 pub mod error;
+pub mod key_opt;
 pub mod native_types;
 pub mod raw;
+pub mod rdb;
 pub mod rediserror;
 mod redismodule;
 pub mod redisraw;
 pub mod redisvalue;
 pub mod stream;
+pub mod zset;
 
 pub mod configuration;
 mod context;
@@ -19,7 +27,13 @@ pub mod logging;
 mod macros;
 mod utils;
 
-pub use crate::context::blocked::BlockedClient;
+pub use crate::args::{FromRedisArg, RedisArgs};
+pub use crate::context::acl::{AclLogReason, ModuleUser};
+pub use crate::context::auth::{AuthResult, BlockedAuthClient};
+pub use crate::context::aux_data::{AuxData, AuxDataBuilder, MAX_AUX_DATA};
+pub use crate::context::blocked::{
+    BlockClientBuilder, BlockClientOnKeysBuilder, BlockOnKeysFlags, BlockedClient,
+};
 pub use crate::context::thread_safe::{
     ContextGuard, DetachedFromClient, RedisGILGuard, RedisLockIndicator, ThreadSafeContext,
 };
@@ -29,9 +43,16 @@ pub use crate::configuration::ConfigurationValue;
 pub use crate::configuration::EnumConfigurationValue;
 pub use crate::context::call_reply::FutureCallReply;
 pub use crate::context::call_reply::{CallReply, CallResult, ErrorReply, PromiseCallReply};
+pub use crate::context::cluster::{ClusterFlags, ClusterNodeFlags, ClusterNodeId, ClusterNodeInfo};
+pub use crate::context::command_filter::{
+    CommandFilter, CommandFilterCtx, CommandFilterFlags, MAX_COMMAND_FILTERS,
+};
 pub use crate::context::commands;
+pub use crate::context::fork::{ChildContext, ForkHandle, ForkResult};
 pub use crate::context::keys_cursor::KeysCursor;
 pub use crate::context::server_events;
+pub use crate::context::server_events::{ClientInfo, ClientInfoFlags};
+pub use crate::context::AclChannelPermissions;
 pub use crate::context::AclPermissions;
 #[cfg(feature = "min-redis-compatibility-version-7-2")]
 pub use crate::context::BlockingCallOptions;
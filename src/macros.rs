@@ -39,6 +39,32 @@ macro_rules! redis_command {
             return $crate::raw::Status::Err as c_int;
         }
     }};
+    ($ctx:expr,
+     $command_name:expr,
+     $command_handler:expr,
+     $command_flags:expr,
+     $firstkey:expr,
+     $lastkey:expr,
+     $keystep:expr,
+     $acl_categories:expr) => {{
+        $crate::redis_command!(
+            $ctx,
+            $command_name,
+            $command_handler,
+            $command_flags,
+            $firstkey,
+            $lastkey,
+            $keystep
+        );
+
+        let context = $crate::Context::new($ctx);
+        if let Err(e) =
+            $crate::commands::set_acl_categories(&context, $command_name, $acl_categories)
+        {
+            context.log_warning(&format!("{e}"));
+            return $crate::raw::Status::Err as c_int;
+        }
+    }};
 }
 
 #[macro_export]
@@ -100,6 +126,9 @@ macro_rules! redis_module {
         $(init: $init_func:ident,)* $(,)*
         $(deinit: $deinit_func:ident,)* $(,)*
         $(info: $info_func:ident,)?
+        $(acl_categories: [
+            $($acl_category:expr),* $(,)*
+        ] $(,)* )?
         commands: [
             $([
                 $name:expr,
@@ -108,6 +137,7 @@ macro_rules! redis_module {
                 $firstkey:expr,
                 $lastkey:expr,
                 $keystep:expr
+                $(, $command_acl_categories:expr)?
               ]),* $(,)*
         ] $(,)*
         $(event_handlers: [
@@ -116,6 +146,21 @@ macro_rules! redis_module {
                 $event_handler:expr
             ]),* $(,)*
         ] $(,)* )?
+        $(command_filters: [
+            $([
+                $command_filter:expr,
+                $command_filter_flags:expr
+            ]),* $(,)*
+        ] $(,)* )?
+        $(cluster_message_receivers: [
+            $([
+                $cluster_message_type:expr,
+                $cluster_message_receiver:expr
+            ]),* $(,)*
+        ] $(,)* )?
+        $(auth: [
+            $($auth_callback:expr),* $(,)*
+        ] $(,)* )?
         $(configurations: [
             $(i64:[$([
                 $i64_configuration_name:expr,
@@ -225,14 +270,27 @@ macro_rules! redis_module {
             }
             let args = $crate::decode_args(ctx, argv, argc);
 
+            $(
+                $(
+                    if let Err(e) = context.add_acl_category($acl_category) {
+                        context.log_warning(&format!("{e}"));
+                        return raw::Status::Err as c_int;
+                    }
+                )*
+            )?
+
             $(
                 if (&$data_type).create_data_type(ctx).is_err() {
                     return raw::Status::Err as c_int;
                 }
             )*
 
+            if $crate::native_types::create_data_types(ctx).is_err() {
+                return raw::Status::Err as c_int;
+            }
+
             $(
-                $crate::redis_command!(ctx, $name, $command, $flags, $firstkey, $lastkey, $keystep);
+                $crate::redis_command!(ctx, $name, $command, $flags, $firstkey, $lastkey, $keystep $(, $command_acl_categories)?);
             )*
 
             if $crate::commands::register_commands(&context) == raw::Status::Err {
@@ -245,6 +303,35 @@ macro_rules! redis_module {
                 )*
             )?
 
+            $(
+                $(
+                    // Filters stay registered until the module is unloaded,
+                    // Redis unregisters them on its own at that point.
+                    match context.register_command_filter($command_filter_flags, $command_filter) {
+                        Ok(filter) => filter.leak(),
+                        Err(e) => {
+                            context.log_warning(&format!("{e}"));
+                            return raw::Status::Err as c_int;
+                        }
+                    }
+                )*
+            )?
+
+            $(
+                $(
+                    context.register_cluster_message_receiver($cluster_message_type, $cluster_message_receiver);
+                )*
+            )?
+
+            $(
+                $(
+                    if let Err(e) = context.register_auth_callback($auth_callback) {
+                        context.log_warning(&format!("{e}"));
+                        return raw::Status::Err as c_int;
+                    }
+                )*
+            )?
+
             $(
                 $(
                     $(
@@ -1,8 +1,22 @@
 use std::cell::RefCell;
 use std::ffi::CString;
+use std::mem::ManuallyDrop;
+use std::os::raw::{c_int, c_void};
 use std::ptr;
 
-use crate::raw;
+use bitflags::bitflags;
+use linkme::distributed_slice;
+use num_traits::FromPrimitive;
+use redis_module_macros_internals::api;
+
+use crate::aof::AofEmitter;
+use crate::defrag::{defrag_callback, Defrag, DefragContext, DefragStatus};
+use crate::digest::Digest;
+use crate::error::Error;
+use crate::key_opt::KeyOptCtx;
+use crate::raw::{self, Aux};
+use crate::rdb::{RdbReader, RdbWriter};
+use crate::{Context, RedisError, RedisString};
 
 pub struct RedisType {
     name: &'static str,
@@ -30,6 +44,98 @@ impl RedisType {
         }
     }
 
+    /// Creates the data type of `T`, with the callbacks of its
+    /// [`RedisDataType`] implementation.
+    ///
+    /// # Panics
+    ///
+    /// Panics, at compile time when used to initialize a `static`, if the
+    /// name of the type is not exactly 9 characters long.
+    #[must_use]
+    pub const fn of<T: RedisDataType>() -> Self {
+        assert!(
+            T::NAME.len() == 9,
+            "Redis requires the length of native type names to be exactly 9 characters"
+        );
+        let callbacks = T::CALLBACKS;
+        let has_aux = callbacks.contains(DataTypeCallbacks::AUX);
+        Self::new(
+            T::NAME,
+            T::ENCODING_VERSION,
+            raw::RedisModuleTypeMethods {
+                version: raw::REDISMODULE_TYPE_METHOD_VERSION as u64,
+                rdb_load: Some(rdb_load::<T>),
+                rdb_save: Some(rdb_save::<T>),
+                aof_rewrite: if callbacks.contains(DataTypeCallbacks::AOF_REWRITE) {
+                    Some(aof_rewrite::<T>)
+                } else {
+                    None
+                },
+                free: Some(free::<T>),
+                mem_usage: if callbacks.contains(DataTypeCallbacks::MEM_USAGE) {
+                    Some(mem_usage_v1::<T>)
+                } else {
+                    None
+                },
+                digest: if callbacks.contains(DataTypeCallbacks::DIGEST) {
+                    Some(digest::<T>)
+                } else {
+                    None
+                },
+                aux_load: if has_aux { Some(aux_load::<T>) } else { None },
+                aux_save: if has_aux { Some(aux_save::<T>) } else { None },
+                aux_save2: None,
+                aux_save_triggers: if has_aux {
+                    T::AUX_SAVE_TRIGGERS.bits()
+                } else {
+                    0
+                },
+                // Redis 7.0 and above use the callbacks with a context below
+                // instead, older versions only know these.
+                free_effort: if callbacks.contains(DataTypeCallbacks::FREE_EFFORT) {
+                    Some(free_effort_v1::<T>)
+                } else {
+                    None
+                },
+                unlink: if callbacks.contains(DataTypeCallbacks::UNLINK) {
+                    Some(unlink_v1::<T>)
+                } else {
+                    None
+                },
+                copy: if callbacks.contains(DataTypeCallbacks::COPY) {
+                    Some(copy_v1::<T>)
+                } else {
+                    None
+                },
+                defrag: if callbacks.contains(DataTypeCallbacks::DEFRAG) {
+                    Some(defrag_callback::<DataTypeDefrag<T>>)
+                } else {
+                    None
+                },
+                copy2: if callbacks.contains(DataTypeCallbacks::COPY) {
+                    Some(copy::<T>)
+                } else {
+                    None
+                },
+                free_effort2: if callbacks.contains(DataTypeCallbacks::FREE_EFFORT) {
+                    Some(free_effort::<T>)
+                } else {
+                    None
+                },
+                mem_usage2: if callbacks.contains(DataTypeCallbacks::MEM_USAGE) {
+                    Some(mem_usage::<T>)
+                } else {
+                    None
+                },
+                unlink2: if callbacks.contains(DataTypeCallbacks::UNLINK) {
+                    Some(unlink::<T>)
+                } else {
+                    None
+                },
+            },
+        )
+    }
+
     #[allow(clippy::not_unsafe_ptr_arg_deref)]
     pub fn create_data_type(&self, ctx: *mut raw::RedisModuleCtx) -> Result<(), &str> {
         if self.name.len() != 9 {
@@ -63,6 +169,359 @@ impl RedisType {
 
         Ok(())
     }
+
+    /// Returns the created type if `T` is the [`RedisDataType`] of this
+    /// type, as its callbacks cast the values to `T`.
+    fn typed_raw_type<T: RedisDataType>(&self) -> Result<*mut raw::RedisModuleType, RedisError> {
+        if !ptr::eq(T::redis_type(), self) {
+            return Err(RedisError::Str("The value is not of this data type"));
+        }
+        let raw_type = *self.raw_type.borrow();
+        if raw_type.is_null() {
+            return Err(RedisError::Str("The data type is not created"));
+        }
+        Ok(raw_type)
+    }
+
+    /// Serializes `value` to a string, in the same encoding as the RDB.
+    /// Returns an error if `T` is not the [`RedisDataType`] of this type.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_SaveDataTypeToString` is missing in redismodule.h
+    pub fn serialize_value<T: RedisDataType>(
+        &self,
+        ctx: &Context,
+        value: &T,
+    ) -> Result<RedisString, RedisError> {
+        let raw_type = self.typed_raw_type::<T>()?;
+        let data = unsafe {
+            raw::RedisModule_SaveDataTypeToString.unwrap()(
+                ctx.ctx,
+                (value as *const T).cast_mut().cast::<c_void>(),
+                raw_type,
+            )
+        };
+        if data.is_null() {
+            return Err(RedisError::Str("Failed serializing the value"));
+        }
+        Ok(RedisString::from_redis_module_string(ctx.ctx, data))
+    }
+
+    /// Deserializes a value serialized with [`RedisType::serialize_value`],
+    /// with the current encoding version of the data type. Returns an error
+    /// if `T` is not the [`RedisDataType`] of this type.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_LoadDataTypeFromString` is missing in redismodule.h
+    pub fn deserialize_value<T: RedisDataType>(
+        &self,
+        data: &RedisString,
+    ) -> Result<Box<T>, RedisError> {
+        let raw_type = self.typed_raw_type::<T>()?;
+        let value =
+            unsafe { raw::RedisModule_LoadDataTypeFromString.unwrap()(data.inner, raw_type) };
+        Self::deserialized_value(value)
+    }
+
+    api!(
+        [RedisModule_LoadDataTypeFromStringEncver],
+        /// Deserializes a value serialized with [`RedisType::serialize_value`]
+        /// by an older encoding version of the data type. Returns an error
+        /// if `T` is not the [`RedisDataType`] of this type.
+        pub fn deserialize_value_encver<T: RedisDataType>(
+            &self,
+            data: &RedisString,
+            encver: i32,
+        ) -> Result<Box<T>, RedisError> {
+            let raw_type = self.typed_raw_type::<T>()?;
+            let value =
+                unsafe { RedisModule_LoadDataTypeFromStringEncver(data.inner, raw_type, encver) };
+            Self::deserialized_value(value)
+        }
+    );
+
+    fn deserialized_value<T: RedisDataType>(value: *mut c_void) -> Result<Box<T>, RedisError> {
+        if value.is_null() {
+            return Err(RedisError::Str("Failed deserializing the value"));
+        }
+        Ok(unsafe { Box::from_raw(value.cast::<T>()) })
+    }
+}
+
+bitflags! {
+    /// The optional callbacks of a [`RedisDataType`]. Only the callbacks
+    /// set here are given to Redis, the `#[redis_data_type]` macro sets the
+    /// ones implemented in the `impl` block.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct DataTypeCallbacks: u32 {
+        const AOF_REWRITE = 1 << 0;
+        const MEM_USAGE = 1 << 1;
+        const DIGEST = 1 << 2;
+        const FREE_EFFORT = 1 << 3;
+        const UNLINK = 1 << 4;
+        const COPY = 1 << 5;
+        /// Both `aux_load` and `aux_save`.
+        const AUX = 1 << 6;
+        const DEFRAG = 1 << 7;
+    }
+}
+
+bitflags! {
+    /// When the `aux_save` callback of a [`RedisDataType`] is called.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct AuxSaveTriggers: c_int {
+        const BEFORE_RDB = raw::REDISMODULE_AUX_BEFORE_RDB as c_int;
+        const AFTER_RDB = raw::REDISMODULE_AUX_AFTER_RDB as c_int;
+    }
+}
+
+/// A custom data type, whose values are stored in Redis keys.
+///
+/// Implement it with the `#[redis_data_type]` macro, which sets the name,
+/// the encoding version and the optional callbacks of the type, and
+/// registers the type when the module is loaded:
+///
+/// ```rust,no_run,ignore
+/// #[redis_data_type({ name: "mytype123", encoding_version: 1 })]
+/// impl RedisDataType for MyType {
+///     fn rdb_load(rdb: &mut RdbReader, _encver: i32) -> Result<Self, Error> {
+///         Ok(MyType { data: rdb.read_string()?.into() })
+///     }
+///
+///     fn rdb_save(&self, rdb: &mut RdbWriter) {
+///         rdb.write_str(&self.data);
+///     }
+/// }
+/// ```
+///
+/// The values of the type are then accessed with
+/// [`crate::key::RedisKey::get_typed_value`] and
+/// [`crate::key::RedisKeyWritable::set_typed_value`].
+pub trait RedisDataType: Sized + 'static {
+    /// The name of the type, exactly 9 characters long.
+    const NAME: &'static str;
+    /// The version of the RDB encoding of the type, given to
+    /// [`Self::rdb_load`] when loading values saved by older versions.
+    const ENCODING_VERSION: i32;
+    /// The optional callbacks implemented by the type.
+    const CALLBACKS: DataTypeCallbacks = DataTypeCallbacks::empty();
+    /// When [`Self::aux_save`] is called, if implemented.
+    const AUX_SAVE_TRIGGERS: AuxSaveTriggers = AuxSaveTriggers::empty();
+
+    /// The registered type, created from [`RedisType::of`].
+    fn redis_type() -> &'static RedisType;
+
+    /// Loads a value from the RDB. Returning an error fails the loading.
+    fn rdb_load(rdb: &mut RdbReader, encver: i32) -> Result<Self, Error>;
+
+    /// Saves the value to the RDB.
+    fn rdb_save(&self, rdb: &mut RdbWriter);
+
+    /// Emits the commands which recreate the value at `key`, when the AOF
+    /// is rewritten.
+    fn aof_rewrite(&self, _aof: &mut AofEmitter, _key: &RedisString) {}
+
+    /// Returns the memory used by the value, in bytes. For collections,
+    /// `sample_size` is the number of elements to sample to estimate it, 0
+    /// to count all of them. Redis versions before 7.0 always give 0.
+    fn mem_usage(&self, _ctx: &KeyOptCtx, _sample_size: usize) -> usize {
+        std::mem::size_of::<Self>()
+    }
+
+    /// Adds the value to the digest computed by `DEBUG DIGEST`.
+    fn digest(&self, _digest: &mut Digest) {}
+
+    /// Returns the effort of freeing the value, usually the number of its
+    /// allocations. With lazy freeing, values with an effort above 64 are
+    /// freed in a background thread.
+    fn free_effort(&self, _ctx: &KeyOptCtx) -> usize {
+        1
+    }
+
+    /// Called when the value is unlinked from its key, before it is freed,
+    /// possibly in a background thread.
+    fn unlink(&self, _ctx: &KeyOptCtx) {}
+
+    /// Returns a copy of the value for `COPY`, or `None` to fail the copy.
+    /// The source and destination keys are given by the context.
+    fn copy(&self, _ctx: &KeyOptCtx) -> Option<Self> {
+        None
+    }
+
+    /// Defragments the allocations owned by the value during the active
+    /// defragmentation of Redis, as [`Defrag::defrag`] does. The value
+    /// itself is moved by Redis, which requires the module to use
+    /// [`crate::alloc::RedisAlloc`] as its global allocator.
+    fn defrag(&mut self, _ctx: &DefragContext) -> DefragStatus {
+        DefragStatus::Done
+    }
+
+    /// Loads the data of the type which is not stored in keys, saved by
+    /// [`Self::aux_save`].
+    fn aux_load(_rdb: &mut RdbReader, _encver: i32, _when: Aux) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Saves the data of the type which is not stored in keys, at the
+    /// points set by [`Self::AUX_SAVE_TRIGGERS`].
+    fn aux_save(_rdb: &mut RdbWriter, _when: Aux) {}
+}
+
+/// The data types registered with the `#[redis_data_type]` macro, created
+/// when the module is loaded.
+#[distributed_slice()]
+pub static DATA_TYPES_LIST: [fn() -> &'static RedisType] = [..];
+
+/// Creates the data types registered with the `#[redis_data_type]` macro.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn create_data_types(ctx: *mut raw::RedisModuleCtx) -> Result<(), &'static str> {
+    DATA_TYPES_LIST
+        .iter()
+        .try_for_each(|redis_type| redis_type().create_data_type(ctx))
+}
+
+/// Borrows a string given to a callback by Redis, without retaining it.
+fn borrow_string(s: *mut raw::RedisModuleString) -> ManuallyDrop<RedisString> {
+    ManuallyDrop::new(RedisString::from_redis_module_string(ptr::null_mut(), s))
+}
+
+unsafe extern "C" fn rdb_load<T: RedisDataType>(
+    rdb: *mut raw::RedisModuleIO,
+    encver: c_int,
+) -> *mut c_void {
+    match T::rdb_load(&mut RdbReader::new(rdb, encver), encver) {
+        Ok(value) => Box::into_raw(Box::new(value)).cast::<c_void>(),
+        Err(e) => {
+            redis_log(
+                ptr::null_mut(),
+                &format!("Failed loading a value of type '{}': {e}", T::NAME),
+            );
+            ptr::null_mut()
+        }
+    }
+}
+
+unsafe extern "C" fn rdb_save<T: RedisDataType>(rdb: *mut raw::RedisModuleIO, value: *mut c_void) {
+    (*value.cast::<T>()).rdb_save(&mut RdbWriter::new(rdb));
+}
+
+unsafe extern "C" fn aof_rewrite<T: RedisDataType>(
+    aof: *mut raw::RedisModuleIO,
+    key: *mut raw::RedisModuleString,
+    value: *mut c_void,
+) {
+    (*value.cast::<T>()).aof_rewrite(&mut AofEmitter::new(aof), &borrow_string(key));
+}
+
+unsafe extern "C" fn free<T: RedisDataType>(value: *mut c_void) {
+    drop(Box::from_raw(value.cast::<T>()));
+}
+
+unsafe extern "C" fn mem_usage<T: RedisDataType>(
+    ctx: *mut raw::RedisModuleKeyOptCtx,
+    value: *const c_void,
+    sample_size: usize,
+) -> usize {
+    (*value.cast::<T>()).mem_usage(&KeyOptCtx::new(ctx), sample_size)
+}
+
+unsafe extern "C" fn digest<T: RedisDataType>(md: *mut raw::RedisModuleDigest, value: *mut c_void) {
+    (*value.cast::<T>()).digest(&mut Digest::new(md));
+}
+
+unsafe extern "C" fn free_effort<T: RedisDataType>(
+    ctx: *mut raw::RedisModuleKeyOptCtx,
+    value: *const c_void,
+) -> usize {
+    (*value.cast::<T>()).free_effort(&KeyOptCtx::new(ctx))
+}
+
+unsafe extern "C" fn unlink<T: RedisDataType>(
+    ctx: *mut raw::RedisModuleKeyOptCtx,
+    value: *const c_void,
+) {
+    (*value.cast::<T>()).unlink(&KeyOptCtx::new(ctx));
+}
+
+unsafe extern "C" fn copy<T: RedisDataType>(
+    ctx: *mut raw::RedisModuleKeyOptCtx,
+    value: *const c_void,
+) -> *mut c_void {
+    (*value.cast::<T>())
+        .copy(&KeyOptCtx::new(ctx))
+        .map_or(ptr::null_mut(), |value| {
+            Box::into_raw(Box::new(value)).cast::<c_void>()
+        })
+}
+
+unsafe extern "C" fn mem_usage_v1<T: RedisDataType>(value: *const c_void) -> usize {
+    (*value.cast::<T>()).mem_usage(&KeyOptCtx::from_keys(ptr::null_mut(), ptr::null_mut()), 0)
+}
+
+unsafe extern "C" fn free_effort_v1<T: RedisDataType>(
+    key: *mut raw::RedisModuleString,
+    value: *const c_void,
+) -> usize {
+    (*value.cast::<T>()).free_effort(&KeyOptCtx::from_keys(key, ptr::null_mut()))
+}
+
+unsafe extern "C" fn unlink_v1<T: RedisDataType>(
+    key: *mut raw::RedisModuleString,
+    value: *const c_void,
+) {
+    (*value.cast::<T>()).unlink(&KeyOptCtx::from_keys(key, ptr::null_mut()));
+}
+
+unsafe extern "C" fn copy_v1<T: RedisDataType>(
+    from_key: *mut raw::RedisModuleString,
+    to_key: *mut raw::RedisModuleString,
+    value: *const c_void,
+) -> *mut c_void {
+    (*value.cast::<T>())
+        .copy(&KeyOptCtx::from_keys(from_key, to_key))
+        .map_or(ptr::null_mut(), |value| {
+            Box::into_raw(Box::new(value)).cast::<c_void>()
+        })
+}
+
+/// A value of a [`RedisDataType`], defragmented by its `defrag` method
+/// through [`defrag_callback`], which takes values stored as `Box<T>`.
+#[repr(transparent)]
+struct DataTypeDefrag<T>(T);
+
+impl<T: RedisDataType> Defrag for DataTypeDefrag<T> {
+    fn defrag(&mut self, ctx: &DefragContext) -> DefragStatus {
+        RedisDataType::defrag(&mut self.0, ctx)
+    }
+}
+
+unsafe extern "C" fn aux_load<T: RedisDataType>(
+    rdb: *mut raw::RedisModuleIO,
+    encver: c_int,
+    when: c_int,
+) -> c_int {
+    let when = match Aux::from_i32(when) {
+        Some(when) => when,
+        None => return raw::Status::Err as c_int,
+    };
+    match T::aux_load(&mut RdbReader::new(rdb, encver), encver, when) {
+        Ok(()) => raw::Status::Ok as c_int,
+        Err(e) => {
+            redis_log(
+                ptr::null_mut(),
+                &format!("Failed loading the aux data of type '{}': {e}", T::NAME),
+            );
+            raw::Status::Err as c_int
+        }
+    }
+}
+
+unsafe extern "C" fn aux_save<T: RedisDataType>(rdb: *mut raw::RedisModuleIO, when: c_int) {
+    if let Some(when) = Aux::from_i32(when) {
+        T::aux_save(&mut RdbWriter::new(rdb), when);
+    }
 }
 
 // TODO: Move to raw
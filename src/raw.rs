@@ -192,6 +192,11 @@ extern "C" {
     ) -> c_int;
 
     pub fn Export_RedisModule_InitAPI(ctx: *mut RedisModuleCtx) -> c_void;
+
+    pub fn Export_RedisModule_SaveLongDouble(io: *mut RedisModuleIO, value: c_double) -> c_int;
+
+    pub fn Export_RedisModule_LoadLongDouble(io: *mut RedisModuleIO, value: *mut c_double)
+        -> c_int;
 }
 
 ///////////////////////////////////////////////////////////////
@@ -725,6 +730,20 @@ pub fn load_float(rdb: *mut RedisModuleIO) -> Result<f32, Error> {
     unsafe { load(rdb, |rdb| RedisModule_LoadFloat.unwrap()(rdb)) }
 }
 
+/// Loads a long double saved with `RedisModule_SaveLongDouble`, converted to
+/// `f64` since Rust has no long double type.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn load_long_double(rdb: *mut RedisModuleIO) -> Result<f64, Error> {
+    let mut value = 0.0;
+    let status: Status = unsafe { Export_RedisModule_LoadLongDouble(rdb, &mut value) }.into();
+    if status == Status::Err {
+        return Err(Error::generic(
+            "RedisModule_LoadLongDouble is not available",
+        ));
+    }
+    load(rdb, |_| value)
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn save_string(rdb: *mut RedisModuleIO, buf: &str) {
     unsafe { RedisModule_SaveStringBuffer.unwrap()(rdb, buf.as_ptr().cast::<c_char>(), buf.len()) };
@@ -762,6 +781,20 @@ pub fn save_unsigned(rdb: *mut RedisModuleIO, val: u64) {
     unsafe { RedisModule_SaveUnsigned.unwrap()(rdb, val) };
 }
 
+/// Saves `val` as a long double, with `RedisModule_SaveLongDouble`.
+///
+/// # Panics
+///
+/// Will panic if `RedisModule_SaveLongDouble` is not available
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn save_long_double(rdb: *mut RedisModuleIO, val: f64) {
+    let status: Status = unsafe { Export_RedisModule_SaveLongDouble(rdb, val) }.into();
+    assert!(
+        status == Status::Ok,
+        "RedisModule_SaveLongDouble is not available"
+    );
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn string_compare(a: *mut RedisModuleString, b: *mut RedisModuleString) -> Ordering {
     unsafe { RedisModule_StringCompare.unwrap()(a, b).cmp(&0) }
@@ -0,0 +1,741 @@
+use redis_module_macros_internals::api;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+
+use crate::error::Error;
+use crate::raw;
+use crate::{Context, RedisBuffer, RedisString};
+
+/// Returns the name of the key the IO is for, if any.
+fn io_key_name(rdb: *mut raw::RedisModuleIO) -> Option<RedisString> {
+    let key_name = unsafe { raw::RedisModule_GetKeyNameFromIO.unwrap()(rdb) };
+    (!key_name.is_null()).then(|| RedisString::new(None, key_name.cast_mut()))
+}
+
+/// Reads the values of a custom data type from the RDB, see
+/// [`crate::native_types::RedisDataType::rdb_load`].
+///
+/// Every read fails with an error once the underlying IO failed, for
+/// example on a truncated RDB file.
+///
+/// Besides the primitive reads, any [`serde::Deserialize`] type can be read
+/// with [`RdbReader::deserialize`], from the layout written by
+/// [`RdbWriter::serialize`].
+pub struct RdbReader {
+    rdb: *mut raw::RedisModuleIO,
+    encver: i32,
+}
+
+impl RdbReader {
+    #[must_use]
+    pub const fn new(rdb: *mut raw::RedisModuleIO, encver: i32) -> Self {
+        Self { rdb, encver }
+    }
+
+    /// The encoding version the value was saved with.
+    #[must_use]
+    pub const fn encver(&self) -> i32 {
+        self.encver
+    }
+
+    /// Returns true if a previous read failed.
+    #[must_use]
+    pub fn is_io_error(&self) -> bool {
+        raw::is_io_error(self.rdb)
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetKeyNameFromIO` is missing in redismodule.h
+    #[must_use]
+    pub fn key_name(&self) -> Option<RedisString> {
+        io_key_name(self.rdb)
+    }
+
+    /// Returns a context for the IO, to log or call Redis APIs which are
+    /// not related to a client.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetContextFromIO` is missing in redismodule.h
+    #[must_use]
+    pub fn context(&self) -> Context {
+        Context::new(unsafe { raw::RedisModule_GetContextFromIO.unwrap()(self.rdb) })
+    }
+
+    api!(
+        [RedisModule_GetDbIdFromIO],
+        /// Returns the id of the database of the key the IO is for.
+        pub fn db_id(&self) -> i32 {
+            unsafe { RedisModule_GetDbIdFromIO(self.rdb) }
+        }
+    );
+
+    pub fn read_unsigned(&mut self) -> Result<u64, Error> {
+        raw::load_unsigned(self.rdb)
+    }
+
+    pub fn read_signed(&mut self) -> Result<i64, Error> {
+        raw::load_signed(self.rdb)
+    }
+
+    pub fn read_double(&mut self) -> Result<f64, Error> {
+        raw::load_double(self.rdb)
+    }
+
+    pub fn read_float(&mut self) -> Result<f32, Error> {
+        raw::load_float(self.rdb)
+    }
+
+    /// Reads a long double, saved with [`RdbWriter::write_long_double`] or
+    /// by a C module, converted to `f64`.
+    pub fn read_long_double(&mut self) -> Result<f64, Error> {
+        raw::load_long_double(self.rdb)
+    }
+
+    pub fn read_string(&mut self) -> Result<RedisString, Error> {
+        raw::load_string(self.rdb)
+    }
+
+    pub fn read_string_buffer(&mut self) -> Result<RedisBuffer, Error> {
+        raw::load_string_buffer(self.rdb)
+    }
+
+    /// Reads a value written with [`RdbWriter::serialize`].
+    pub fn deserialize<T: DeserializeOwned>(&mut self) -> Result<T, Error> {
+        T::deserialize(self)
+    }
+
+    /// Reads a value which was added to the encoding in version `encver`.
+    /// Values saved with an older encoding version do not have it, and get
+    /// `default` instead.
+    pub fn deserialize_since<T: DeserializeOwned>(
+        &mut self,
+        encver: i32,
+        default: T,
+    ) -> Result<T, Error> {
+        if self.encver < encver {
+            return Ok(default);
+        }
+        self.deserialize()
+    }
+
+    fn read_len(&mut self) -> Result<usize, Error> {
+        let len = self.read_unsigned()?;
+        usize::try_from(len).map_err(|_| Error::generic("Length does not fit in usize"))
+    }
+}
+
+/// Writes the values of a custom data type to the RDB, see
+/// [`crate::native_types::RedisDataType::rdb_save`].
+///
+/// Besides the primitive writes, any [`serde::Serialize`] type can be written
+/// with [`RdbWriter::serialize`]. The layout is compact and not
+/// self-describing: values are written in order, without field names, so
+/// changing the serialized types requires a new encoding version of the data
+/// type, see [`RdbReader::encver`].
+pub struct RdbWriter {
+    rdb: *mut raw::RedisModuleIO,
+}
+
+impl RdbWriter {
+    #[must_use]
+    pub const fn new(rdb: *mut raw::RedisModuleIO) -> Self {
+        Self { rdb }
+    }
+
+    /// Returns true if a previous write failed.
+    #[must_use]
+    pub fn is_io_error(&self) -> bool {
+        raw::is_io_error(self.rdb)
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetKeyNameFromIO` is missing in redismodule.h
+    #[must_use]
+    pub fn key_name(&self) -> Option<RedisString> {
+        io_key_name(self.rdb)
+    }
+
+    /// Returns a context for the IO, to log or call Redis APIs which are
+    /// not related to a client.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetContextFromIO` is missing in redismodule.h
+    #[must_use]
+    pub fn context(&self) -> Context {
+        Context::new(unsafe { raw::RedisModule_GetContextFromIO.unwrap()(self.rdb) })
+    }
+
+    api!(
+        [RedisModule_GetDbIdFromIO],
+        /// Returns the id of the database of the key the IO is for.
+        pub fn db_id(&self) -> i32 {
+            unsafe { RedisModule_GetDbIdFromIO(self.rdb) }
+        }
+    );
+
+    pub fn write_unsigned(&mut self, value: u64) {
+        raw::save_unsigned(self.rdb, value);
+    }
+
+    pub fn write_signed(&mut self, value: i64) {
+        raw::save_signed(self.rdb, value);
+    }
+
+    pub fn write_double(&mut self, value: f64) {
+        raw::save_double(self.rdb, value);
+    }
+
+    pub fn write_float(&mut self, value: f32) {
+        raw::save_float(self.rdb, value);
+    }
+
+    /// Writes `value` as a long double, readable by C modules with
+    /// `RedisModule_LoadLongDouble`.
+    pub fn write_long_double(&mut self, value: f64) {
+        raw::save_long_double(self.rdb, value);
+    }
+
+    pub fn write_string(&mut self, value: &RedisString) {
+        raw::save_redis_string(self.rdb, value);
+    }
+
+    pub fn write_str(&mut self, value: &str) {
+        raw::save_string(self.rdb, value);
+    }
+
+    pub fn write_slice(&mut self, value: &[u8]) {
+        raw::save_slice(self.rdb, value);
+    }
+
+    /// Writes `value`, to be read back with [`RdbReader::deserialize`].
+    ///
+    /// Fails on types the layout cannot represent, such as sequences of
+    /// unknown length.
+    pub fn serialize<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+}
+
+impl ser::Serializer for &mut RdbWriter {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.write_unsigned(u64::from(v));
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.write_signed(v);
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.write_unsigned(v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.write_float(v);
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.write_double(v);
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.write_str(v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.write_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.write_unsigned(0);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        self.write_unsigned(1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Error> {
+        self.serialize_u32(variant_index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.write_unsigned(u64::from(variant_index));
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, Error> {
+        let len = len.ok_or_else(|| Error::generic("Sequences must have a known length"))?;
+        self.write_unsigned(len as u64);
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.write_unsigned(u64::from(variant_index));
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, Error> {
+        let len = len.ok_or_else(|| Error::generic("Maps must have a known length"))?;
+        self.write_unsigned(len as u64);
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.write_unsigned(u64::from(variant_index));
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl ser::SerializeSeq for &mut RdbWriter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut RdbWriter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut RdbWriter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut RdbWriter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut RdbWriter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut RdbWriter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut RdbWriter {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut RdbReader {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::generic(
+            "The RDB layout is not self-describing, the type must be known",
+        ))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(self.read_unsigned()? != 0)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(self.read_signed()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(self.read_unsigned()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f32(self.read_float()?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(self.read_double()?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let value = self.read_string_buffer()?.to_string()?;
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(Error::generic("Expected a single character")),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.read_string_buffer()?.to_string()?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_byte_buf(self.read_string_buffer()?.as_ref().to_vec())
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.read_unsigned()? {
+            0 => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let len = self.read_len()?;
+        visitor.visit_seq(SeqAccess { rdb: self, len })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(SeqAccess { rdb: self, len })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let len = self.read_len()?;
+        visitor.visit_map(SeqAccess { rdb: self, len })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u32(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::generic(
+            "The RDB layout is not self-describing, values cannot be skipped",
+        ))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Reads the `len` elements of a sequence, or the `len` entries of a map.
+struct SeqAccess<'a> {
+    rdb: &'a mut RdbReader,
+    len: usize,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for SeqAccess<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.rdb).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de, 'a> de::MapAccess<'de> for SeqAccess<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.rdb).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.rdb)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut RdbReader {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant_index = u32::try_from(self.read_unsigned()?)
+            .map_err(|_| Error::generic("Invalid enum variant index"))?;
+        let value =
+            seed.deserialize(IntoDeserializer::<Error>::into_deserializer(variant_index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut RdbReader {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
@@ -11,3 +11,25 @@ int Export_RedisModule_Init(RedisModuleCtx *ctx, const char *name, int ver, int
 void Export_RedisModule_InitAPI(RedisModuleCtx *ctx) {
     RedisModule_InitAPI(ctx);
 }
+
+// The long double functions are left out of redismodule.h since Rust has no
+// long double type. Look them up by name and convert from and to double, so
+// they can be called from Rust.
+
+int Export_RedisModule_SaveLongDouble(RedisModuleIO *io, double value) {
+    void (*save)(RedisModuleIO *, long double) = NULL;
+    if (RedisModule_GetApi("RedisModule_SaveLongDouble", (void **)&save) == REDISMODULE_ERR || save == NULL) {
+        return REDISMODULE_ERR;
+    }
+    save(io, value);
+    return REDISMODULE_OK;
+}
+
+int Export_RedisModule_LoadLongDouble(RedisModuleIO *io, double *value) {
+    long double (*load)(RedisModuleIO *) = NULL;
+    if (RedisModule_GetApi("RedisModule_LoadLongDouble", (void **)&load) == REDISMODULE_ERR || load == NULL) {
+        return REDISMODULE_ERR;
+    }
+    *value = (double)load(io);
+    return REDISMODULE_OK;
+}
@@ -0,0 +1,175 @@
+use crate::raw;
+use crate::RedisError;
+use crate::RedisString;
+use crate::Status;
+use bitflags::bitflags;
+use std::marker::PhantomData;
+use std::os::raw::{c_double, c_int};
+use std::ptr;
+
+bitflags! {
+    /// Input and output flags of [`crate::key::RedisKeyWritable::zset_add`] and
+    /// [`crate::key::RedisKeyWritable::zset_incrby`].
+    ///
+    /// `XX`, `NX`, `GT` and `LT` are input flags with the same meaning as the
+    /// matching `ZADD` options. `ADDED`, `UPDATED` and `NOP` are set by Redis
+    /// on return to report what happened to the element.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ZaddFlags: c_int {
+        const XX = raw::REDISMODULE_ZADD_XX as c_int;
+        const NX = raw::REDISMODULE_ZADD_NX as c_int;
+        const GT = raw::REDISMODULE_ZADD_GT as c_int;
+        const LT = raw::REDISMODULE_ZADD_LT as c_int;
+        const ADDED = raw::REDISMODULE_ZADD_ADDED as c_int;
+        const UPDATED = raw::REDISMODULE_ZADD_UPDATED as c_int;
+        const NOP = raw::REDISMODULE_ZADD_NOP as c_int;
+    }
+}
+
+/// One end of a score range, see [`ZsetRangeIterator`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoreBound {
+    Inclusive(f64),
+    Exclusive(f64),
+    /// `-inf` when used as the minimum, `+inf` when used as the maximum.
+    Unbounded,
+}
+
+impl ScoreBound {
+    fn to_raw(self, unbounded: f64) -> (c_double, c_int) {
+        match self {
+            Self::Inclusive(score) => (score, 0),
+            Self::Exclusive(score) => (score, 1),
+            Self::Unbounded => (unbounded, 0),
+        }
+    }
+}
+
+/// One end of a lexicographical range, see [`ZsetRangeIterator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexBound<'a> {
+    Inclusive(&'a [u8]),
+    Exclusive(&'a [u8]),
+    /// `-` when used as the minimum, `+` when used as the maximum.
+    Unbounded,
+}
+
+impl<'a> LexBound<'a> {
+    /// Builds the `ZRANGEBYLEX` style representation of the bound.
+    fn to_redis_string(self, unbounded: &[u8]) -> RedisString {
+        let bound = match self {
+            Self::Inclusive(value) => [b"[", value].concat(),
+            Self::Exclusive(value) => [b"(", value].concat(),
+            Self::Unbounded => unbounded.to_vec(),
+        };
+        RedisString::create_from_slice(ptr::null_mut(), &bound)
+    }
+}
+
+#[derive(Debug)]
+pub struct ZsetElement {
+    pub element: RedisString,
+    pub score: f64,
+}
+
+/// Iterates over a range of a sorted set, either by score or
+/// lexicographically, forward or in reverse.
+///
+/// The iterator borrows the key it was created from, so the sorted set can
+/// not be modified while it is being iterated.
+#[derive(Debug)]
+pub struct ZsetRangeIterator<'key> {
+    key_inner: *mut raw::RedisModuleKey,
+    reverse: bool,
+    phantom: PhantomData<&'key raw::RedisModuleKey>,
+}
+
+impl<'key> ZsetRangeIterator<'key> {
+    pub(crate) fn new_score_range(
+        key_inner: *mut raw::RedisModuleKey,
+        min: ScoreBound,
+        max: ScoreBound,
+        reverse: bool,
+    ) -> Result<ZsetRangeIterator<'key>, RedisError> {
+        let (min, minex) = min.to_raw(raw::REDISMODULE_NEGATIVE_INFINITE);
+        let (max, maxex) = max.to_raw(raw::REDISMODULE_POSITIVE_INFINITE);
+        let res = unsafe {
+            if reverse {
+                raw::RedisModule_ZsetLastInScoreRange.unwrap()(key_inner, min, max, minex, maxex)
+            } else {
+                raw::RedisModule_ZsetFirstInScoreRange.unwrap()(key_inner, min, max, minex, maxex)
+            }
+        };
+        Self::started(key_inner, res, reverse)
+    }
+
+    pub(crate) fn new_lex_range(
+        key_inner: *mut raw::RedisModuleKey,
+        min: LexBound,
+        max: LexBound,
+        reverse: bool,
+    ) -> Result<ZsetRangeIterator<'key>, RedisError> {
+        // Redis parses the bounds right away, so the strings can be freed as
+        // soon as the iterator is positioned.
+        let min = min.to_redis_string(b"-");
+        let max = max.to_redis_string(b"+");
+        let res = unsafe {
+            if reverse {
+                raw::RedisModule_ZsetLastInLexRange.unwrap()(key_inner, min.inner, max.inner)
+            } else {
+                raw::RedisModule_ZsetFirstInLexRange.unwrap()(key_inner, min.inner, max.inner)
+            }
+        };
+        Self::started(key_inner, res, reverse)
+    }
+
+    fn started(
+        key_inner: *mut raw::RedisModuleKey,
+        res: c_int,
+        reverse: bool,
+    ) -> Result<ZsetRangeIterator<'key>, RedisError> {
+        if Status::Ok == res.into() {
+            Ok(ZsetRangeIterator {
+                key_inner,
+                reverse,
+                phantom: PhantomData,
+            })
+        } else {
+            Err(RedisError::Str("Failed creating sorted set range iterator"))
+        }
+    }
+}
+
+impl<'key> Iterator for ZsetRangeIterator<'key> {
+    type Item = ZsetElement;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if unsafe { raw::RedisModule_ZsetRangeEndReached.unwrap()(self.key_inner) } != 0 {
+            return None;
+        }
+        let mut score: c_double = 0.0;
+        let element = unsafe {
+            raw::RedisModule_ZsetRangeCurrentElement.unwrap()(self.key_inner, &mut score)
+        };
+        if element.is_null() {
+            return None;
+        }
+        unsafe {
+            if self.reverse {
+                raw::RedisModule_ZsetRangePrev.unwrap()(self.key_inner);
+            } else {
+                raw::RedisModule_ZsetRangeNext.unwrap()(self.key_inner);
+            }
+        }
+        Some(ZsetElement {
+            element: RedisString::from_redis_module_string(ptr::null_mut(), element),
+            score,
+        })
+    }
+}
+
+impl<'key> Drop for ZsetRangeIterator<'key> {
+    fn drop(&mut self) {
+        unsafe { raw::RedisModule_ZsetRangeStop.unwrap()(self.key_inner) };
+    }
+}
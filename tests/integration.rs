@@ -1,8 +1,11 @@
-use crate::utils::{get_redis_connection, start_redis_server_with_module};
+use crate::utils::{
+    get_redis_connection, start_redis_server_with_module, start_redis_server_with_module_and_args,
+};
 use anyhow::Context;
 use anyhow::Result;
 use redis::Value;
 use redis::{RedisError, RedisResult};
+use std::collections::HashMap;
 
 mod utils;
 
@@ -358,6 +361,130 @@ fn test_verify_acl_on_user() -> Result<()> {
         );
     }
 
+    let res: String = redis::cmd("verify_command_access_for_user")
+        .arg(&["alice", "get", "cached:1"])
+        .query(&mut con)?;
+    assert_eq!(&res, "OK");
+
+    let res: RedisResult<String> = redis::cmd("verify_command_access_for_user")
+        .arg(&["alice", "set", "cached:1", "1"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    let res: String = redis::cmd("ACL")
+        .arg(&["SETUSER", "alice", "resetchannels", "&news:*"])
+        .query(&mut con)?;
+    assert_eq!(&res, "OK");
+
+    let res: String = redis::cmd("verify_channel_access_for_user")
+        .arg(&["alice", "news:1"])
+        .query(&mut con)?;
+    assert_eq!(&res, "OK");
+
+    let res: RedisResult<String> = redis::cmd("verify_channel_access_for_user")
+        .arg(&["alice", "sports:1"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_module_user() -> Result<()> {
+    let port: u16 = 6512;
+    let _guards = vec![start_redis_server_with_module("acl", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+    let mut admin_con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let _: i64 = redis::cmd("sso.login")
+        .arg(&["bob", "on ~bob:* +get +set +get_current_user"])
+        .query(&mut con)
+        .with_context(|| "failed to run sso.login")?;
+    let res: String = redis::cmd("get_current_user").query(&mut con)?;
+    assert_eq!(&res, "bob");
+
+    let res: String = redis::cmd("SET")
+        .arg(&["bob:1", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run SET")?;
+    assert_eq!(&res, "OK");
+    let res: RedisResult<String> = redis::cmd("SET").arg(&["alice:1", "1"]).query(&mut con);
+    assert!(res.is_err());
+
+    let res: String = redis::cmd("sso.acl")
+        .arg(&["bob"])
+        .query(&mut admin_con)
+        .with_context(|| "failed to run sso.acl")?;
+    assert!(res.contains("~bob:*"));
+
+    let res: RedisResult<i64> = redis::cmd("sso.login")
+        .arg(&["bob", "on +nosuchcommand"])
+        .query(&mut admin_con);
+    assert!(res.is_err());
+
+    // Deleting the user disconnects its client.
+    let res: bool = redis::cmd("sso.logout")
+        .arg(&["bob"])
+        .query(&mut admin_con)
+        .with_context(|| "failed to run sso.logout")?;
+    assert!(res);
+    let res: RedisResult<String> = redis::cmd("get_current_user").query(&mut con);
+    assert!(res.is_err());
+
+    let res: i64 = redis::cmd("sso.deauthenticated")
+        .query(&mut admin_con)
+        .with_context(|| "failed to run sso.deauthenticated")?;
+    assert_eq!(res, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_acl_log_entry() -> Result<()> {
+    let port: u16 = 6514;
+    let _guards = vec![start_redis_server_with_module("acl", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+    let mut alice_con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let res: String = redis::cmd("ACL")
+        .arg(&["SETUSER", "alice", "on", ">pass", "~cached:*", "+@read"])
+        .query(&mut con)?;
+    assert_eq!(&res, "OK");
+    let res: String = redis::cmd("AUTH")
+        .arg(&["alice", "pass"])
+        .query(&mut alice_con)?;
+    assert_eq!(&res, "OK");
+
+    // guarded_get is given the read category by redis_command!.
+    let res: Vec<String> = redis::cmd("ACL").arg(&["CAT", "read"]).query(&mut con)?;
+    assert!(res.iter().any(|command| command == "guarded_get"));
+
+    let res: Option<String> = redis::cmd("guarded_get")
+        .arg(&["cached:1"])
+        .query(&mut alice_con)?;
+    assert_eq!(res, None);
+    let res: RedisResult<Option<String>> = redis::cmd("guarded_get")
+        .arg(&["secret"])
+        .query(&mut alice_con);
+    assert!(res.is_err());
+
+    // The denial shows up in ACL LOG as the built-in ones do.
+    let entries: Vec<HashMap<String, String>> = redis::cmd("ACL")
+        .arg(&["LOG"])
+        .query(&mut con)
+        .with_context(|| "failed to run ACL LOG")?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["reason"], "key");
+    assert_eq!(entries[0]["context"], "module");
+    assert_eq!(entries[0]["object"], "secret");
+    assert_eq!(entries[0]["username"], "alice");
+
     Ok(())
 }
 
@@ -453,6 +580,42 @@ fn test_server_event() -> Result<()> {
 
     assert!(res > 0);
 
+    let connected_clients: i64 = redis::cmd("num_connected_clients").query(&mut con)?;
+    assert!(connected_clients > 0);
+    let _con2 = get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+    let res: i64 = redis::cmd("num_connected_clients").query(&mut con)?;
+    assert_eq!(res, connected_clients + 1);
+
+    redis::cmd("swapdb")
+        .arg(&["0", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run swapdb")?;
+    let res: i64 = redis::cmd("num_swapdbs").query(&mut con)?;
+    assert_eq!(res, 1);
+
+    redis::cmd("save")
+        .query(&mut con)
+        .with_context(|| "failed to run save")?;
+    let res: i64 = redis::cmd("num_saves").query(&mut con)?;
+    assert_eq!(res, 1);
+
+    redis::cmd("set")
+        .arg(&["x", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run set")?;
+    redis::cmd("set")
+        .arg(&["x", "2"])
+        .query(&mut con)
+        .with_context(|| "failed to run set")?;
+    redis::cmd("del")
+        .arg(&["x"])
+        .query(&mut con)
+        .with_context(|| "failed to run del")?;
+    let res: i64 = redis::cmd("num_overwritten_keys").query(&mut con)?;
+    assert_eq!(res, 1);
+    let res: i64 = redis::cmd("num_deleted_keys").query(&mut con)?;
+    assert_eq!(res, 1);
+
     Ok(())
 }
 
@@ -646,3 +809,1236 @@ fn test_call_blocking() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_zset() -> Result<()> {
+    let port: u16 = 6501;
+    let _guards = vec![start_redis_server_with_module("zset", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    for (score, member) in [("1", "a"), ("2", "b"), ("3", "c")] {
+        let res: i64 = redis::cmd("LEADERBOARD.ADD")
+            .arg(&["board", score, member])
+            .query(&mut con)
+            .with_context(|| "failed to run LEADERBOARD.ADD")?;
+        assert_eq!(res, 1);
+    }
+
+    let res: i64 = redis::cmd("LEADERBOARD.ADD")
+        .arg(&["board", "4", "c"])
+        .query(&mut con)
+        .with_context(|| "failed to run LEADERBOARD.ADD")?;
+    assert_eq!(res, 0);
+
+    let res: f64 = redis::cmd("LEADERBOARD.INCR")
+        .arg(&["board", "1.5", "a"])
+        .query(&mut con)
+        .with_context(|| "failed to run LEADERBOARD.INCR")?;
+    assert_eq!(res, 2.5);
+
+    let res: Option<f64> = redis::cmd("LEADERBOARD.INCR")
+        .arg(&["board", "1", "missing"])
+        .query(&mut con)
+        .with_context(|| "failed to run LEADERBOARD.INCR")?;
+    assert_eq!(res, None);
+
+    let res: Vec<String> = redis::cmd("LEADERBOARD.RANGE")
+        .arg(&["board", "(2", "+inf"])
+        .query(&mut con)
+        .with_context(|| "failed to run LEADERBOARD.RANGE")?;
+    assert_eq!(res, vec!["a", "2.5", "c", "4"]);
+
+    let res: Vec<String> = redis::cmd("LEADERBOARD.RANGE")
+        .arg(&["board", "-inf", "2.5", "REV"])
+        .query(&mut con)
+        .with_context(|| "failed to run LEADERBOARD.RANGE")?;
+    assert_eq!(res, vec!["a", "2.5", "b", "2"]);
+
+    let res: i64 = redis::cmd("LEADERBOARD.REM")
+        .arg(&["board", "b"])
+        .query(&mut con)
+        .with_context(|| "failed to run LEADERBOARD.REM")?;
+    assert_eq!(res, 1);
+
+    let res: Option<f64> = redis::cmd("LEADERBOARD.SCORE")
+        .arg(&["board", "b"])
+        .query(&mut con)
+        .with_context(|| "failed to run LEADERBOARD.SCORE")?;
+    assert_eq!(res, None);
+
+    for member in ["x", "y", "z"] {
+        let _: i64 = redis::cmd("LEADERBOARD.ADD")
+            .arg(&["names", "0", member])
+            .query(&mut con)
+            .with_context(|| "failed to run LEADERBOARD.ADD")?;
+    }
+
+    let res: Vec<String> = redis::cmd("LEADERBOARD.LEXRANGE")
+        .arg(&["names", "(x", "+"])
+        .query(&mut con)
+        .with_context(|| "failed to run LEADERBOARD.LEXRANGE")?;
+    assert_eq!(res, vec!["y", "0", "z", "0"]);
+
+    let res: Vec<String> = redis::cmd("LEADERBOARD.LEXRANGE")
+        .arg(&["names", "-", "[y", "REV"])
+        .query(&mut con)
+        .with_context(|| "failed to run LEADERBOARD.LEXRANGE")?;
+    assert_eq!(res, vec!["y", "0", "x", "0"]);
+
+    let _: () = redis::cmd("SET")
+        .arg(&["str", "value"])
+        .query(&mut con)
+        .with_context(|| "failed to run SET")?;
+    let res: Result<Option<f64>, RedisError> = redis::cmd("LEADERBOARD.SCORE")
+        .arg(&["str", "a"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_dict() -> Result<()> {
+    let port: u16 = 6502;
+    let _guards = vec![start_redis_server_with_module("dict", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    for (name, value) in [("b", 2), ("a", 1), ("d", 4), ("c", 3)] {
+        let res: Option<i64> = redis::cmd("INDEX.SET")
+            .arg(name)
+            .arg(value)
+            .query(&mut con)
+            .with_context(|| "failed to run INDEX.SET")?;
+        assert_eq!(res, None);
+    }
+
+    let res: Option<i64> = redis::cmd("INDEX.SET")
+        .arg(&["c", "30"])
+        .query(&mut con)
+        .with_context(|| "failed to run INDEX.SET")?;
+    assert_eq!(res, Some(3));
+
+    let res: Option<i64> = redis::cmd("INDEX.GET")
+        .arg(&["c"])
+        .query(&mut con)
+        .with_context(|| "failed to run INDEX.GET")?;
+    assert_eq!(res, Some(30));
+
+    let res: Vec<String> = redis::cmd("INDEX.RANGE")
+        .arg(&["^", "", "10"])
+        .query(&mut con)
+        .with_context(|| "failed to run INDEX.RANGE")?;
+    assert_eq!(res, vec!["a", "1", "b", "2", "c", "30", "d", "4"]);
+
+    let res: Vec<String> = redis::cmd("INDEX.RANGE")
+        .arg(&[">", "a", "2"])
+        .query(&mut con)
+        .with_context(|| "failed to run INDEX.RANGE")?;
+    assert_eq!(res, vec!["b", "2", "c", "30"]);
+
+    let res: Vec<String> = redis::cmd("INDEX.RANGE")
+        .arg(&["<=", "c", "10", "REV"])
+        .query(&mut con)
+        .with_context(|| "failed to run INDEX.RANGE")?;
+    assert_eq!(res, vec!["c", "30", "b", "2", "a", "1"]);
+
+    let res: i64 = redis::cmd("INDEX.DEL")
+        .arg(&["b"])
+        .query(&mut con)
+        .with_context(|| "failed to run INDEX.DEL")?;
+    assert_eq!(res, 1);
+
+    let res: Vec<String> = redis::cmd("INDEX.RANGE")
+        .arg(&["$", "", "10", "REV"])
+        .query(&mut con)
+        .with_context(|| "failed to run INDEX.RANGE")?;
+    assert_eq!(res, vec!["d", "4", "c", "30", "a", "1"]);
+
+    let res: Result<Vec<String>, RedisError> = redis::cmd("INDEX.RANGE")
+        .arg(&["!", "a", "1"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_command_filter() -> Result<()> {
+    let port: u16 = 6503;
+    let _guards = vec![start_redis_server_with_module("command_filter", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let res: String = redis::cmd("LEGACY.SET")
+        .arg(&["x", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run LEGACY.SET")?;
+    assert_eq!(res, "OK");
+
+    let res: String = redis::cmd("GET")
+        .arg(&["x"])
+        .query(&mut con)
+        .with_context(|| "failed to run GET")?;
+    assert_eq!(res, "1");
+
+    let res: String = redis::cmd("LEGACY.SETEX")
+        .arg(&["y", "100", "2"])
+        .query(&mut con)
+        .with_context(|| "failed to run LEGACY.SETEX")?;
+    assert_eq!(res, "OK");
+
+    let res: String = redis::cmd("GET")
+        .arg(&["y"])
+        .query(&mut con)
+        .with_context(|| "failed to run GET")?;
+    assert_eq!(res, "2");
+
+    let res: i64 = redis::cmd("TTL")
+        .arg(&["y"])
+        .query(&mut con)
+        .with_context(|| "failed to run TTL")?;
+    assert!(res > 0 && res <= 100);
+
+    let res: i64 = redis::cmd("FILTER.REWRITES")
+        .query(&mut con)
+        .with_context(|| "failed to run FILTER.REWRITES")?;
+    assert_eq!(res, 2);
+
+    let res: Result<String, RedisError> =
+        redis::cmd("LEGACY.SETEX").arg(&["z", "1"]).query(&mut con);
+    assert!(res.is_err());
+
+    // The one shot filter unregisters itself from its callback.
+    let res: i64 = redis::cmd("FILTER.ONESHOT")
+        .query(&mut con)
+        .with_context(|| "failed to run FILTER.ONESHOT")?;
+    assert_eq!(res, 0);
+    for _ in 0..2 {
+        redis::cmd("PING")
+            .query::<()>(&mut con)
+            .with_context(|| "failed to run PING")?;
+    }
+    let res: i64 = redis::cmd("FILTER.ONESHOT")
+        .query(&mut con)
+        .with_context(|| "failed to run FILTER.ONESHOT")?;
+    assert_eq!(res, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_cluster_disabled() -> Result<()> {
+    let port: u16 = 6504;
+    let _guards = vec![start_redis_server_with_module("cluster", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let res: (Option<String>, i64) = redis::cmd("COORD.INFO")
+        .query(&mut con)
+        .with_context(|| "failed to run COORD.INFO")?;
+    assert_eq!(res, (None, 0));
+
+    let res: Result<Vec<Vec<String>>, RedisError> = redis::cmd("COORD.NODES").query(&mut con);
+    assert!(res.is_err());
+
+    let res: Result<String, RedisError> = redis::cmd("COORD.SEND").arg(&["hello"]).query(&mut con);
+    assert!(res.is_err());
+
+    let res: Result<String, RedisError> = redis::cmd("COORD.SEND")
+        .arg(&["hello", "not-a-node-id"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    let res: Vec<String> = redis::cmd("COORD.NOTES")
+        .query(&mut con)
+        .with_context(|| "failed to run COORD.NOTES")?;
+    assert!(res.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_block_on_keys() -> Result<()> {
+    let port: u16 = 6505;
+    let _guards = vec![start_redis_server_with_module("block_on_keys", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let res: String = redis::cmd("QUEUE.PUSH")
+        .arg(&["queue", "a"])
+        .query(&mut con)
+        .with_context(|| "failed to run QUEUE.PUSH")?;
+    assert_eq!(res, "OK");
+
+    let res: (String, i64) = redis::cmd("QUEUE.POP")
+        .arg(&["queue", "0"])
+        .query(&mut con)
+        .with_context(|| "failed to run QUEUE.POP")?;
+    assert_eq!(res, ("a".to_string(), 0));
+
+    let res: Option<(String, i64)> = redis::cmd("QUEUE.POP")
+        .arg(&["queue", "100"])
+        .query(&mut con)
+        .with_context(|| "failed to run QUEUE.POP")?;
+    assert_eq!(res, None);
+
+    let pusher = std::thread::spawn(move || -> Result<()> {
+        let mut con =
+            get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        let _: String = redis::cmd("QUEUE.PUSH")
+            .arg(&["queue", "b"])
+            .query(&mut con)
+            .with_context(|| "failed to run QUEUE.PUSH")?;
+        Ok(())
+    });
+
+    let (element, waited): (String, i64) = redis::cmd("QUEUE.POP")
+        .arg(&["queue", "5000"])
+        .query(&mut con)
+        .with_context(|| "failed to run QUEUE.POP")?;
+    assert_eq!(element, "b");
+    assert!(waited > 0);
+    pusher.join().unwrap()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_block() -> Result<()> {
+    let port: u16 = 6506;
+    let _guards = vec![start_redis_server_with_module("block", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let res: String = redis::cmd("block")
+        .query(&mut con)
+        .with_context(|| "failed to run block")?;
+    assert_eq!(res, "42");
+
+    let res: i64 = redis::cmd("block.sleep")
+        .arg(&["50", "0"])
+        .query(&mut con)
+        .with_context(|| "failed to run block.sleep")?;
+    assert_eq!(res, 5);
+
+    let res: Result<i64, RedisError> = redis::cmd("block.sleep")
+        .arg(&["1000", "50"])
+        .query(&mut con);
+    assert!(res
+        .err()
+        .and_then(|e| e.detail().map(|d| d.contains("timed out")))
+        .unwrap_or(false));
+
+    let res: Result<i64, RedisError> = redis::cmd("block.drop").query(&mut con);
+    assert!(res
+        .err()
+        .and_then(|e| e.detail().map(|d| d.contains("dropped without a reply")))
+        .unwrap_or(false));
+
+    // The time spent in the background thread is accounted to the command.
+    let _: String = redis::cmd("SLOWLOG")
+        .arg(&["RESET"])
+        .query(&mut con)
+        .with_context(|| "failed to run SLOWLOG RESET")?;
+    let _: i64 = redis::cmd("block.sleep")
+        .arg(&["50", "0"])
+        .query(&mut con)
+        .with_context(|| "failed to run block.sleep")?;
+    let res: i64 = redis::cmd("SLOWLOG")
+        .arg(&["LEN"])
+        .query(&mut con)
+        .with_context(|| "failed to run SLOWLOG LEN")?;
+    assert_eq!(res, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_fork() -> Result<()> {
+    let port: u16 = 6507;
+    let _guards = vec![start_redis_server_with_module("fork", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    redis::cmd("MSET")
+        .arg(&["x", "1", "y", "2"])
+        .query(&mut con)
+        .with_context(|| "failed to run MSET")?;
+
+    let path = std::env::temp_dir().join(format!("fork-dump-{port}.txt"));
+    let path = path.to_str().unwrap();
+    let pid: i64 = redis::cmd("FORK.DUMP")
+        .arg(&[path, "x", "missing", "y"])
+        .query(&mut con)
+        .with_context(|| "failed to run FORK.DUMP")?;
+    assert!(pid > 0);
+
+    let mut status: Option<(i64, bool)> = None;
+    for _ in 0..50 {
+        status = redis::cmd("FORK.STATUS")
+            .query(&mut con)
+            .with_context(|| "failed to run FORK.STATUS")?;
+        if status.is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert_eq!(status, Some((0, false)));
+
+    let dump = std::fs::read_to_string(path)?;
+    std::fs::remove_file(path)?;
+    assert_eq!(dump, "1\n\n2\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_defrag() -> Result<()> {
+    let port: u16 = 6508;
+    let _guards = vec![start_redis_server_with_module("defrag", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let res: i64 = redis::cmd("DEFRAG.ADD")
+        .arg(&["index", "a", "b"])
+        .query(&mut con)
+        .with_context(|| "failed to run DEFRAG.ADD")?;
+    assert_eq!(res, 2);
+
+    let res: i64 = redis::cmd("DEFRAG.ADD")
+        .arg(&["index", "c"])
+        .query(&mut con)
+        .with_context(|| "failed to run DEFRAG.ADD")?;
+    assert_eq!(res, 3);
+
+    let res: i64 = redis::cmd("DEFRAG.LEN")
+        .arg(&["index"])
+        .query(&mut con)
+        .with_context(|| "failed to run DEFRAG.LEN")?;
+    assert_eq!(res, 3);
+
+    let res: i64 = redis::cmd("DEFRAG.TAG")
+        .arg(&["tags", "x", "y"])
+        .query(&mut con)
+        .with_context(|| "failed to run DEFRAG.TAG")?;
+    assert_eq!(res, 2);
+
+    // Make active defrag run regardless of the fragmentation, as the
+    // defrag tests of Redis do. It is only available with jemalloc.
+    let res: RedisResult<String> = redis::cmd("CONFIG")
+        .arg(&["SET", "activedefrag", "yes"])
+        .query(&mut con);
+    if let Err(err) = &res {
+        if err.to_string().contains("DISABLED") {
+            println!("Skipping test_defrag, active defrag is not supported");
+            return Ok(());
+        }
+    }
+    res.with_context(|| "failed to enable active defrag")?;
+    for (name, value) in [
+        ("active-defrag-ignore-bytes", "1"),
+        ("active-defrag-threshold-lower", "0"),
+        ("active-defrag-cycle-min", "99"),
+    ] {
+        redis::cmd("CONFIG")
+            .arg(&["SET", name, value])
+            .query::<()>(&mut con)
+            .with_context(|| format!("failed to set {name}"))?;
+    }
+
+    // The values and the global data of the module are defragmented.
+    let mut res: Vec<i64> = Vec::new();
+    for _ in 0..100 {
+        res = redis::cmd("DEFRAG.STATS")
+            .query(&mut con)
+            .with_context(|| "failed to run DEFRAG.STATS")?;
+        if res[0] > 0 && res[2] > 0 && res[3] > 0 {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert!(res[0] > 0, "the value was not defragmented: {res:?}");
+    assert!(res[2] > 0, "the global data was not defragmented: {res:?}");
+    assert!(res[3] > 0, "the typed value was not defragmented: {res:?}");
+
+    // The value is still intact.
+    let res: i64 = redis::cmd("DEFRAG.LEN")
+        .arg(&["index"])
+        .query(&mut con)
+        .with_context(|| "failed to run DEFRAG.LEN")?;
+    assert_eq!(res, 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_typed_data_type() -> Result<()> {
+    let port: u16 = 6509;
+    // The AOF is written to a temporary directory.
+    let dir = std::env::temp_dir().join("typed_data_type");
+    std::fs::create_dir_all(&dir)?;
+    let _guards = vec![start_redis_server_with_module_and_args(
+        "typed_data_type",
+        port,
+        &[
+            "--enable-debug-command",
+            "yes",
+            "--dir",
+            dir.to_str().unwrap(),
+        ],
+    )
+    .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let res: i64 = redis::cmd("STRLIST.PUSH")
+        .arg(&["list", "a", "b"])
+        .query(&mut con)
+        .with_context(|| "failed to run STRLIST.PUSH")?;
+    assert_eq!(res, 2);
+
+    let res: String = redis::cmd("TYPE")
+        .arg(&["list"])
+        .query(&mut con)
+        .with_context(|| "failed to run TYPE")?;
+    assert_eq!(res, "strlist01");
+
+    // DUMP and RESTORE go through rdb_save and rdb_load.
+    let dump: Vec<u8> = redis::cmd("DUMP")
+        .arg(&["list"])
+        .query(&mut con)
+        .with_context(|| "failed to run DUMP")?;
+    redis::cmd("RESTORE")
+        .arg("restored")
+        .arg(0)
+        .arg(dump)
+        .query(&mut con)
+        .with_context(|| "failed to run RESTORE")?;
+    let res: Vec<String> = redis::cmd("STRLIST.RANGE")
+        .arg(&["restored"])
+        .query(&mut con)
+        .with_context(|| "failed to run STRLIST.RANGE")?;
+    assert_eq!(res, vec!["a", "b"]);
+
+    let res: bool = redis::cmd("COPY")
+        .arg(&["list", "copied"])
+        .query(&mut con)
+        .with_context(|| "failed to run COPY")?;
+    assert!(res);
+    let res: Vec<String> = redis::cmd("STRLIST.RANGE")
+        .arg(&["copied"])
+        .query(&mut con)
+        .with_context(|| "failed to run STRLIST.RANGE")?;
+    assert_eq!(res, vec!["a", "b"]);
+
+    let data: Vec<u8> = redis::cmd("STRLIST.EXPORT")
+        .arg(&["list"])
+        .query(&mut con)
+        .with_context(|| "failed to run STRLIST.EXPORT")?;
+    redis::cmd("STRLIST.IMPORT")
+        .arg("imported")
+        .arg(data)
+        .query(&mut con)
+        .with_context(|| "failed to run STRLIST.IMPORT")?;
+    let res: Vec<String> = redis::cmd("STRLIST.RANGE")
+        .arg(&["imported"])
+        .query(&mut con)
+        .with_context(|| "failed to run STRLIST.RANGE")?;
+    assert_eq!(res, vec!["a", "b"]);
+    let res: Result<(), RedisError> = redis::cmd("STRLIST.IMPORT")
+        .arg(&["imported", "invalid"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    let res: i64 = redis::cmd("MEMORY")
+        .arg(&["USAGE", "list"])
+        .query(&mut con)
+        .with_context(|| "failed to run MEMORY USAGE")?;
+    assert!(res > 0);
+
+    let res: Vec<i64> = redis::cmd("COUNTERS.INCR")
+        .arg(&["counters", "a"])
+        .query(&mut con)
+        .with_context(|| "failed to run COUNTERS.INCR")?;
+    assert_eq!(res, vec![1, 1]);
+    let res: Vec<i64> = redis::cmd("COUNTERS.INCR")
+        .arg(&["counters", "b"])
+        .query(&mut con)
+        .with_context(|| "failed to run COUNTERS.INCR")?;
+    assert_eq!(res, vec![1, 2]);
+
+    // The counters are serialized with serde.
+    let dump: Vec<u8> = redis::cmd("DUMP")
+        .arg(&["counters"])
+        .query(&mut con)
+        .with_context(|| "failed to run DUMP")?;
+    redis::cmd("RESTORE")
+        .arg("restored_counters")
+        .arg(0)
+        .arg(dump)
+        .query(&mut con)
+        .with_context(|| "failed to run RESTORE")?;
+    let res: Vec<i64> = redis::cmd("COUNTERS.INCR")
+        .arg(&["restored_counters", "a"])
+        .query(&mut con)
+        .with_context(|| "failed to run COUNTERS.INCR")?;
+    assert_eq!(res, vec![2, 3]);
+
+    // Equal values have equal digests, whatever the order of the counters.
+    let digest_value = |con: &mut redis::Connection, keys: &[&str]| -> Result<Vec<String>> {
+        redis::cmd("DEBUG")
+            .arg("DIGEST-VALUE")
+            .arg(keys)
+            .query(con)
+            .with_context(|| "failed to run DEBUG DIGEST-VALUE")
+    };
+    let res = digest_value(&mut con, &["list", "restored", "copied"])?;
+    assert_eq!(res[0], res[1]);
+    assert_eq!(res[0], res[2]);
+    redis::cmd("STRLIST.PUSH")
+        .arg(&["reversed", "b", "a"])
+        .query(&mut con)
+        .with_context(|| "failed to run STRLIST.PUSH")?;
+    let res = digest_value(&mut con, &["list", "reversed"])?;
+    assert_ne!(res[0], res[1]);
+
+    for name in ["b", "a"] {
+        redis::cmd("COUNTERS.INCR")
+            .arg(&["reordered_counters", name])
+            .query(&mut con)
+            .with_context(|| "failed to run COUNTERS.INCR")?;
+    }
+    let res = digest_value(&mut con, &["counters", "reordered_counters"])?;
+    assert_eq!(res[0], res[1]);
+
+    // Enabling the AOF rewrites it with aof_rewrite, reload the values from
+    // it.
+    redis::cmd("CONFIG")
+        .arg(&["SET", "appendonly", "yes"])
+        .query(&mut con)
+        .with_context(|| "failed to run CONFIG SET")?;
+    loop {
+        let info: String = redis::cmd("INFO")
+            .arg("persistence")
+            .query(&mut con)
+            .with_context(|| "failed to run INFO")?;
+        if info.contains("aof_rewrite_in_progress:0") && info.contains("aof_rewrite_scheduled:0") {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    redis::cmd("DEBUG")
+        .arg("LOADAOF")
+        .query(&mut con)
+        .with_context(|| "failed to run DEBUG LOADAOF")?;
+    let res: Vec<String> = redis::cmd("STRLIST.RANGE")
+        .arg(&["list"])
+        .query(&mut con)
+        .with_context(|| "failed to run STRLIST.RANGE")?;
+    assert_eq!(res, vec!["a", "b"]);
+    let res: Vec<i64> = redis::cmd("COUNTERS.INCR")
+        .arg(&["restored_counters", "b"])
+        .query(&mut con)
+        .with_context(|| "failed to run COUNTERS.INCR")?;
+    assert_eq!(res, vec![2, 4]);
+
+    // Lists with more than 64 items are freed in a background thread.
+    let items: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+    redis::cmd("STRLIST.PUSH")
+        .arg("large")
+        .arg(&items)
+        .query(&mut con)
+        .with_context(|| "failed to run STRLIST.PUSH")?;
+    let res: i64 = redis::cmd("UNLINK")
+        .arg(&["large"])
+        .query(&mut con)
+        .with_context(|| "failed to run UNLINK")?;
+    assert_eq!(res, 1);
+    loop {
+        let info: String = redis::cmd("INFO")
+            .arg("memory")
+            .query(&mut con)
+            .with_context(|| "failed to run INFO")?;
+        let lazyfreed = info
+            .lines()
+            .find_map(|line| line.strip_prefix("lazyfreed_objects:"))
+            .and_then(|count| count.trim().parse::<i64>().ok());
+        if lazyfreed.unwrap_or(0) > 0 {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    redis::cmd("SET")
+        .arg(&["string", "value"])
+        .query(&mut con)
+        .with_context(|| "failed to run SET")?;
+    let res: Result<i64, RedisError> = redis::cmd("STRLIST.PUSH")
+        .arg(&["string", "a"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_aux_data() -> Result<()> {
+    let master_port: u16 = 6510;
+    let replica_port: u16 = 6511;
+    let _guards = vec![
+        start_redis_server_with_module("aux_data", master_port)
+            .with_context(|| "failed to start redis server")?,
+        start_redis_server_with_module("aux_data", replica_port)
+            .with_context(|| "failed to start redis server")?,
+    ];
+    let mut master_con =
+        get_redis_connection(master_port).with_context(|| "failed to connect to redis server")?;
+    let mut replica_con =
+        get_redis_connection(replica_port).with_context(|| "failed to connect to redis server")?;
+
+    let res: String = redis::cmd("CATALOG.SET")
+        .arg(&["name", "value"])
+        .query(&mut master_con)
+        .with_context(|| "failed to run CATALOG.SET")?;
+    assert_eq!(res, "OK");
+
+    // The catalog reaches the replica through the RDB of the full sync.
+    redis::cmd("REPLICAOF")
+        .arg("127.0.0.1")
+        .arg(master_port)
+        .query(&mut replica_con)
+        .with_context(|| "failed to run REPLICAOF")?;
+    let mut res: Option<String> = None;
+    for _ in 0..100 {
+        res = redis::cmd("CATALOG.GET")
+            .arg(&["name"])
+            .query(&mut replica_con)
+            .with_context(|| "failed to run CATALOG.GET")?;
+        if res.is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert_eq!(res.as_deref(), Some("value"));
+
+    Ok(())
+}
+
+#[test]
+fn test_auth() -> Result<()> {
+    let port: u16 = 6513;
+    let _guards = vec![start_redis_server_with_module("auth", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    // The users authenticated by the module have no passwords.
+    for user in ["local_admin", "ldap_alice", "ldap_bob"] {
+        let res: String = redis::cmd("ACL")
+            .arg(&["SETUSER", user, "on", "~*", "+@all"])
+            .query(&mut con)
+            .with_context(|| "failed to run ACL SETUSER")?;
+        assert_eq!(&res, "OK");
+    }
+    let res: String = redis::cmd("ACL")
+        .arg(&["SETUSER", "carol", "on", ">carol_pass", "~*", "+@all"])
+        .query(&mut con)
+        .with_context(|| "failed to run ACL SETUSER")?;
+    assert_eq!(&res, "OK");
+
+    let mut auth = |user: &str, password: &str| -> Result<String> {
+        redis::cmd("AUTH")
+            .arg(&[user, password])
+            .query::<String>(&mut con)
+            .with_context(|| "failed to run AUTH")?;
+        redis::cmd("auth.whoami")
+            .query(&mut con)
+            .with_context(|| "failed to run auth.whoami")
+    };
+
+    assert_eq!(auth("local_admin", "admin_pass")?, "local_admin");
+    assert!(auth("local_admin", "wrong")
+        .unwrap_err()
+        .root_cause()
+        .to_string()
+        .contains("local_admin"));
+
+    // The directory users are authenticated on a background thread.
+    assert_eq!(auth("ldap_alice", "alice_pass")?, "ldap_alice");
+    assert!(auth("ldap_bob", "alice_pass").is_err());
+
+    // Other users are left to their ACL passwords.
+    assert_eq!(auth("carol", "carol_pass")?, "carol");
+    assert!(auth("carol", "wrong").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_client() -> Result<()> {
+    let port: u16 = 6515;
+    let _guards = vec![start_redis_server_with_module("client", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+    let mut other =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let id: u64 = redis::cmd("CLIENT")
+        .arg(&["ID"])
+        .query(&mut con)
+        .with_context(|| "failed to run CLIENT ID")?;
+    let res: u64 = redis::cmd("client.id")
+        .query(&mut con)
+        .with_context(|| "failed to run client.id")?;
+    assert_eq!(res, id);
+
+    let info: HashMap<String, redis::Value> = redis::cmd("client.info")
+        .arg(id)
+        .query(&mut other)
+        .with_context(|| "failed to run client.info")?;
+    assert_eq!(info["id"], redis::Value::Int(id as i64));
+    assert_eq!(info["addr"], redis::Value::Data(b"127.0.0.1".to_vec()));
+    assert_eq!(info["db"], redis::Value::Int(0));
+    assert_eq!(info["flags"], redis::Value::Bulk(vec![]));
+    assert!(redis::cmd("client.info")
+        .arg(u64::MAX)
+        .query::<redis::Value>(&mut other)
+        .is_err());
+
+    // The name of a client can be set by another client.
+    let res: Option<String> = redis::cmd("client.name")
+        .arg(id)
+        .query(&mut other)
+        .with_context(|| "failed to run client.name")?;
+    assert_eq!(res, None);
+    let res: String = redis::cmd("client.setname")
+        .arg(&[id.to_string().as_str(), "limited"])
+        .query(&mut other)
+        .with_context(|| "failed to run client.setname")?;
+    assert_eq!(&res, "OK");
+    let res: Option<String> = redis::cmd("CLIENT")
+        .arg(&["GETNAME"])
+        .query(&mut con)
+        .with_context(|| "failed to run CLIENT GETNAME")?;
+    assert_eq!(res.as_deref(), Some("limited"));
+    assert!(redis::cmd("client.setname")
+        .arg(&[id.to_string().as_str(), "not valid"])
+        .query::<String>(&mut other)
+        .is_err());
+
+    let res: String = redis::cmd("client.username")
+        .arg(id)
+        .query(&mut other)
+        .with_context(|| "failed to run client.username")?;
+    assert_eq!(&res, "default");
+    let res: Option<String> = redis::cmd("client.cert")
+        .arg(id)
+        .query(&mut other)
+        .with_context(|| "failed to run client.cert")?;
+    assert_eq!(res, None);
+
+    // Clients are counted by name, or by address when they have none.
+    for expected in 1..=2 {
+        let res: i64 = redis::cmd("client.hit")
+            .query(&mut con)
+            .with_context(|| "failed to run client.hit")?;
+        assert_eq!(res, expected);
+    }
+    let res: i64 = redis::cmd("client.hit")
+        .query(&mut other)
+        .with_context(|| "failed to run client.hit")?;
+    assert_eq!(res, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_pubsub() -> Result<()> {
+    let port: u16 = 6516;
+    let _guards = vec![start_redis_server_with_module("pubsub", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+    let mut subscriber =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let _: Value = redis::cmd("SUBSCRIBE")
+        .arg(&["news:sports"])
+        .query(&mut subscriber)
+        .with_context(|| "failed to run SUBSCRIBE")?;
+    let res: i64 = redis::cmd("notify.publish")
+        .arg(&["news:sports", "goal"])
+        .query(&mut con)
+        .with_context(|| "failed to run notify.publish")?;
+    assert_eq!(res, 1);
+    let res: Vec<String> = redis::from_redis_value(&subscriber.recv_response()?)?;
+    assert_eq!(res, ["message", "news:sports", "goal"]);
+
+    let _: Value = redis::cmd("SSUBSCRIBE")
+        .arg(&["orders"])
+        .query(&mut subscriber)
+        .with_context(|| "failed to run SSUBSCRIBE")?;
+    let res: i64 = redis::cmd("notify.spublish")
+        .arg(&["orders", "created"])
+        .query(&mut con)
+        .with_context(|| "failed to run notify.spublish")?;
+    assert_eq!(res, 1);
+    let res: Vec<String> = redis::from_redis_value(&subscriber.recv_response()?)?;
+    assert_eq!(res, ["smessage", "orders", "created"]);
+
+    // The channels of the commands are checked against the ACL of the user.
+    let res: String = redis::cmd("ACL")
+        .arg(&[
+            "SETUSER",
+            "notifier",
+            "on",
+            "nopass",
+            "resetchannels",
+            "&news:*",
+            "+@all",
+        ])
+        .query(&mut con)
+        .with_context(|| "failed to run ACL SETUSER")?;
+    assert_eq!(&res, "OK");
+    let res: String = redis::cmd("AUTH")
+        .arg(&["notifier", "pass"])
+        .query(&mut con)
+        .with_context(|| "failed to run AUTH")?;
+    assert_eq!(&res, "OK");
+
+    let res: i64 = redis::cmd("notify.publish")
+        .arg(&["news:weather", "rain"])
+        .query(&mut con)
+        .with_context(|| "failed to run notify.publish")?;
+    assert_eq!(res, 0);
+    for command in ["notify.publish", "notify.spublish"] {
+        let res = redis::cmd(command)
+            .arg(&["orders", "cancelled"])
+            .query::<i64>(&mut con)
+            .unwrap_err();
+        assert!(res.to_string().contains("channel"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_subcommands() -> Result<()> {
+    let port: u16 = 6517;
+    let _guards = vec![start_redis_server_with_module("subcommands", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let res: String = redis::cmd("admin.config")
+        .arg(&["set", "mode", "strict"])
+        .query(&mut con)
+        .with_context(|| "failed to run admin.config set")?;
+    assert_eq!(&res, "OK");
+    let res: Option<String> = redis::cmd("admin.config")
+        .arg(&["get", "mode"])
+        .query(&mut con)
+        .with_context(|| "failed to run admin.config get")?;
+    assert_eq!(res.as_deref(), Some("strict"));
+    let res: Option<String> = redis::cmd("admin.config")
+        .arg(&["get", "missing"])
+        .query(&mut con)
+        .with_context(|| "failed to run admin.config get")?;
+    assert_eq!(res, None);
+
+    // The container command only has subcommands.
+    assert!(redis::cmd("admin.config")
+        .arg(&["unknown"])
+        .query::<String>(&mut con)
+        .is_err());
+    assert!(redis::cmd("admin.config")
+        .arg(&["get"])
+        .query::<String>(&mut con)
+        .is_err());
+
+    let res: Vec<Vec<Value>> = redis::cmd("COMMAND")
+        .arg(&["INFO", "admin.config|set"])
+        .query(&mut con)
+        .with_context(|| "failed to run COMMAND INFO")?;
+    assert_eq!(res[0][0], Value::Data(b"admin.config|set".to_vec()));
+    assert_eq!(res[0][1], Value::Int(4));
+    let flags: Vec<String> = redis::from_redis_value(&res[0][2])?;
+    assert!(flags.contains(&"admin".to_string()));
+    let acl_categories: Vec<String> = redis::from_redis_value(&res[0][6])?;
+    assert!(acl_categories.contains(&"@dangerous".to_string()));
+
+    // The subcommands are allowed by their own ACL categories.
+    let res: String = redis::cmd("ACL")
+        .arg(&[
+            "SETUSER",
+            "operator",
+            "on",
+            "nopass",
+            "+@all",
+            "-@dangerous",
+        ])
+        .query(&mut con)
+        .with_context(|| "failed to run ACL SETUSER")?;
+    assert_eq!(&res, "OK");
+    let mut operator_con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+    let res: String = redis::cmd("AUTH")
+        .arg(&["operator", "pass"])
+        .query(&mut operator_con)
+        .with_context(|| "failed to run AUTH")?;
+    assert_eq!(&res, "OK");
+    let res: Option<String> = redis::cmd("admin.config")
+        .arg(&["get", "mode"])
+        .query(&mut operator_con)
+        .with_context(|| "failed to run admin.config get")?;
+    assert_eq!(res.as_deref(), Some("strict"));
+    assert!(redis::cmd("admin.config")
+        .arg(&["set", "mode", "lenient"])
+        .query::<String>(&mut operator_con)
+        .is_err());
+
+    let res: Vec<String> = redis::cmd("COMMAND")
+        .arg(&["GETKEYS", "admin.key", "exists", "x"])
+        .query(&mut con)
+        .with_context(|| "failed to run COMMAND GETKEYS")?;
+    assert_eq!(&res, &["x"]);
+    let res: i64 = redis::cmd("admin.key")
+        .arg(&["exists", "x"])
+        .query(&mut con)
+        .with_context(|| "failed to run admin.key exists")?;
+    assert_eq!(res, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_acl_categories() -> Result<()> {
+    // Modules can add their own ACL categories since Redis 7.4.
+    let output = std::process::Command::new("redis-server")
+        .arg("--version")
+        .output()
+        .with_context(|| "failed to run redis-server --version")?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    let version: Vec<u32> = output
+        .split("v=")
+        .nth(1)
+        .and_then(|v| v.split_whitespace().next())
+        .map(|v| v.split('.').filter_map(|n| n.parse().ok()).collect())
+        .unwrap_or_default();
+    if version < vec![7, 4] {
+        println!("Skipping test_acl_categories, module ACL categories require Redis 7.4");
+        return Ok(());
+    }
+
+    let port: u16 = 6518;
+    let _guards = vec![start_redis_server_with_module("acl_categories", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let mut res: Vec<String> = redis::cmd("ACL")
+        .arg(&["CAT", "accounts"])
+        .query(&mut con)
+        .with_context(|| "failed to run ACL CAT")?;
+    res.sort();
+    assert_eq!(&res, &["accounts.balance", "accounts.deposit"]);
+
+    let res: String = redis::cmd("ACL")
+        .arg(&["SETUSER", "teller", "on", "nopass", "+@accounts"])
+        .query(&mut con)
+        .with_context(|| "failed to run ACL SETUSER")?;
+    assert_eq!(&res, "OK");
+    let res: String = redis::cmd("AUTH")
+        .arg(&["teller", "pass"])
+        .query(&mut con)
+        .with_context(|| "failed to run AUTH")?;
+    assert_eq!(&res, "OK");
+
+    let res: i64 = redis::cmd("accounts.deposit")
+        .arg(&["10"])
+        .query(&mut con)
+        .with_context(|| "failed to run accounts.deposit")?;
+    assert_eq!(res, 10);
+    let res: i64 = redis::cmd("accounts.balance")
+        .query(&mut con)
+        .with_context(|| "failed to run accounts.balance")?;
+    assert_eq!(res, 10);
+    assert!(redis::cmd("GET")
+        .arg(&["x"])
+        .query::<Option<String>>(&mut con)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_command_docs() -> Result<()> {
+    let port: u16 = 6519;
+    let _guards = vec![start_redis_server_with_module("proc_macro_commands", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let res: HashMap<String, HashMap<String, Value>> = redis::cmd("COMMAND")
+        .arg(&["DOCS", "docs_keys"])
+        .query(&mut con)
+        .with_context(|| "failed to run COMMAND DOCS")?;
+    let docs = &res["docs_keys"];
+
+    let history: Vec<Vec<String>> = redis::from_redis_value(&docs["history"])?;
+    assert_eq!(history, [["1.1.0", "Added the `NX` and `XX` options."]]);
+
+    let args: Vec<HashMap<String, Value>> = redis::from_redis_value(&docs["arguments"])?;
+    let names: Vec<String> = args
+        .iter()
+        .map(|arg| redis::from_redis_value(&arg["name"]))
+        .collect::<RedisResult<_>>()?;
+    assert_eq!(names, ["key", "condition", "count", "member"]);
+    let arg_type: String = redis::from_redis_value(&args[0]["type"])?;
+    assert_eq!(arg_type, "key");
+    assert_eq!(args[0]["key_spec_index"], Value::Int(0));
+    let arg_type: String = redis::from_redis_value(&args[1]["type"])?;
+    assert_eq!(arg_type, "oneof");
+    let flags: Vec<String> = redis::from_redis_value(&args[1]["flags"])?;
+    assert_eq!(flags, ["optional"]);
+    let subargs: Vec<HashMap<String, Value>> = redis::from_redis_value(&args[1]["arguments"])?;
+    let tokens: Vec<String> = subargs
+        .iter()
+        .map(|arg| redis::from_redis_value(&arg["token"]))
+        .collect::<RedisResult<_>>()?;
+    assert_eq!(tokens, ["NX", "XX"]);
+    let token: String = redis::from_redis_value(&args[2]["token"])?;
+    assert_eq!(token, "COUNT");
+
+    Ok(())
+}
+
+#[test]
+fn test_redis_args() -> Result<()> {
+    let port: u16 = 6520;
+    let _guards = vec![start_redis_server_with_module("args", port)
+        .with_context(|| "failed to start redis server")?];
+    let mut con =
+        get_redis_connection(port).with_context(|| "failed to connect to redis server")?;
+
+    let res: i64 = redis::cmd("scores.add")
+        .arg(&["board", "3", "carol", "1", "alice", "2", "bob"])
+        .query(&mut con)
+        .with_context(|| "failed to run scores.add")?;
+    assert_eq!(res, 3);
+
+    let res: i64 = redis::cmd("scores.add")
+        .arg(&["board", "nx", "5", "alice", "4", "dave"])
+        .query(&mut con)
+        .with_context(|| "failed to run scores.add")?;
+    assert_eq!(res, 1);
+
+    let res: i64 = redis::cmd("scores.add")
+        .arg(&["board", "XX", "0", "alice", "6", "erin"])
+        .query(&mut con)
+        .with_context(|| "failed to run scores.add")?;
+    assert_eq!(res, 0);
+
+    let res: Vec<String> = redis::cmd("scores.range")
+        .arg(&["board"])
+        .query(&mut con)
+        .with_context(|| "failed to run scores.range")?;
+    assert_eq!(res, ["alice", "bob", "carol", "dave"]);
+
+    let res: Vec<String> = redis::cmd("scores.range")
+        .arg(&["board", "withscores", "LIMIT", "1", "2", "MIN", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run scores.range")?;
+    assert_eq!(res, ["carol", "3", "dave", "4"]);
+
+    let res: RedisResult<Vec<String>> = redis::cmd("scores.range")
+        .arg(&["board", "MIN", "1", "MIN", "2"])
+        .query(&mut con);
+    assert!(res.unwrap_err().to_string().contains("syntax error"));
+
+    let res: RedisResult<Vec<String>> = redis::cmd("scores.range")
+        .arg(&["board", "LIMIT"])
+        .query(&mut con);
+    assert!(res.unwrap_err().to_string().contains("syntax error"));
+
+    let res: RedisResult<Vec<String>> = redis::cmd("scores.range")
+        .arg(&["board", "REV"])
+        .query(&mut con);
+    assert!(res.unwrap_err().to_string().contains("syntax error"));
+
+    let res: RedisResult<i64> = redis::cmd("scores.add")
+        .arg(&["board", "1", "frank", "2"])
+        .query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("wrong number of arguments"));
+
+    let res: RedisResult<i64> = redis::cmd("scores.add")
+        .arg(&["board", "high", "frank"])
+        .query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("Couldn't parse as float"));
+
+    let res: Vec<String> = redis::cmd("scores.incr")
+        .arg(&["board", "by", "2", "bob", "frank"])
+        .query(&mut con)
+        .with_context(|| "failed to run scores.incr")?;
+    assert_eq!(res, ["4", "2"]);
+
+    let res: RedisResult<Vec<String>> = redis::cmd("scores.incr")
+        .arg(&["board", "2", "alice", "frank"])
+        .query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("wrong number of arguments"));
+
+    let res: HashMap<String, HashMap<String, Value>> = redis::cmd("COMMAND")
+        .arg(&["DOCS", "scores.range"])
+        .query(&mut con)
+        .with_context(|| "failed to run COMMAND DOCS")?;
+    let args: Vec<HashMap<String, Value>> =
+        redis::from_redis_value(&res["scores.range"]["arguments"])?;
+    let names: Vec<String> = args
+        .iter()
+        .map(|arg| redis::from_redis_value(&arg["name"]))
+        .collect::<RedisResult<_>>()?;
+    assert_eq!(names, ["board", "min", "limit", "with_scores"]);
+    let types: Vec<String> = args
+        .iter()
+        .map(|arg| redis::from_redis_value(&arg["type"]))
+        .collect::<RedisResult<_>>()?;
+    assert_eq!(types, ["key", "double", "block", "pure-token"]);
+    let token: String = redis::from_redis_value(&args[2]["token"])?;
+    assert_eq!(token, "LIMIT");
+    let subargs: Vec<HashMap<String, Value>> = redis::from_redis_value(&args[2]["arguments"])?;
+    assert_eq!(subargs.len(), 2);
+
+    let res: HashMap<String, HashMap<String, Value>> = redis::cmd("COMMAND")
+        .arg(&["DOCS", "scores.add"])
+        .query(&mut con)
+        .with_context(|| "failed to run COMMAND DOCS")?;
+    let args: Vec<HashMap<String, Value>> =
+        redis::from_redis_value(&res["scores.add"]["arguments"])?;
+    let arg_type: String = redis::from_redis_value(&args[1]["type"])?;
+    assert_eq!(arg_type, "oneof");
+    let flags: Vec<String> = redis::from_redis_value(&args[2]["flags"])?;
+    assert_eq!(flags, ["multiple"]);
+
+    Ok(())
+}
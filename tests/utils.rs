@@ -24,6 +24,14 @@ impl Drop for ChildGuard {
 }
 
 pub fn start_redis_server_with_module(module_name: &str, port: u16) -> Result<ChildGuard> {
+    start_redis_server_with_module_and_args(module_name, port, &[])
+}
+
+pub fn start_redis_server_with_module_and_args(
+    module_name: &str,
+    port: u16,
+    extra_args: &[&str],
+) -> Result<ChildGuard> {
     let extension = if cfg!(target_os = "macos") {
         "dylib"
     } else {
@@ -60,6 +68,7 @@ pub fn start_redis_server_with_module(module_name: &str, port: u16) -> Result<Ch
 
     let redis_server = Command::new("redis-server")
         .args(args)
+        .args(extra_args)
         .spawn()
         .map(|c| ChildGuard {
             name: "redis-server",